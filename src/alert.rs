@@ -0,0 +1,65 @@
+/// Tracks whether a value stayed above a limit for a number of consecutive
+/// samples, so short spikes don't make the display flash.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Threshold {
+    limit: f32,
+    sustain: u32,
+    exceeded_for: u32,
+}
+
+impl Threshold {
+    pub fn new(limit: f32, sustain: u32) -> Self {
+        Self {
+            limit,
+            sustain: sustain.max(1),
+            exceeded_for: 0,
+        }
+    }
+
+    pub fn observe(&mut self, value: f32) {
+        if value > self.limit {
+            self.exceeded_for = self.exceeded_for.saturating_add(1);
+        } else {
+            self.exceeded_for = 0;
+        }
+    }
+
+    pub fn exceeded(&self) -> bool {
+        self.exceeded_for >= self.sustain
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_spike_does_not_alert() {
+        let mut threshold = Threshold::new(10.0, 3);
+        threshold.observe(50.0);
+        threshold.observe(5.0);
+        threshold.observe(50.0);
+
+        assert!(!threshold.exceeded());
+    }
+
+    #[test]
+    fn sustained_values_alert_until_they_drop() {
+        let mut threshold = Threshold::new(10.0, 3);
+        for _ in 0..3 {
+            threshold.observe(11.0);
+        }
+        assert!(threshold.exceeded());
+
+        threshold.observe(10.0);
+        assert!(!threshold.exceeded());
+    }
+
+    #[test]
+    fn zero_sustain_alerts_on_first_sample() {
+        let mut threshold = Threshold::new(10.0, 0);
+        threshold.observe(11.0);
+
+        assert!(threshold.exceeded());
+    }
+}
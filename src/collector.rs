@@ -0,0 +1,394 @@
+use crate::components::Component;
+use crate::Error;
+
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::time::{Duration, Instant};
+
+/// The outcome of an update on a worker thread
+struct Update {
+    index: usize,
+    worker: u64,
+    snapshot: Box<dyn Component>,
+    interval: Option<Duration>,
+}
+
+struct Slot {
+    requests: Sender<()>,
+    snapshot: Box<dyn Component>,
+    configured: Option<Duration>,
+    // the current worker, updates from abandoned ones are dropped
+    worker: u64,
+    requested: Option<Instant>,
+    // an abandoned worker is still stuck in its update
+    hung: bool,
+    stale: bool,
+}
+
+/// Updates every component on a thread of its own, so a hanging update (say
+/// a statfs on an unreachable NFS mount) only affects that component. The
+/// display draws the latest snapshot of each component. Updates that take
+/// longer than `timeout` mark it as stale and their worker is abandoned for
+/// a new one that starts from the latest snapshot.
+pub struct Collector {
+    timeout: Duration,
+    slots: Vec<Slot>,
+    workers: u64,
+    updates: Sender<Update>,
+    results: Receiver<Update>,
+    finished: Vec<(usize, Option<Duration>)>,
+}
+
+impl Collector {
+    pub fn new(timeout: Duration) -> Self {
+        let (updates, results) = channel();
+        Self {
+            timeout,
+            slots: Vec::new(),
+            workers: 0,
+            updates,
+            results,
+            finished: Vec::new(),
+        }
+    }
+
+    /// Starts the worker for `component`, updated every `configured` interval
+    /// if set, and requests its first update. Returns the index used to
+    /// request further updates.
+    pub fn add(
+        &mut self,
+        component: Box<dyn Component>,
+        configured: Option<Duration>,
+    ) -> Result<usize, Error> {
+        let index = self.slots.len();
+        let snapshot = component.snapshot();
+        let worker = self.next_worker();
+        let requests = self.spawn(index, worker, component, configured)?;
+
+        self.slots.push(Slot {
+            requests,
+            snapshot,
+            configured,
+            worker,
+            requested: None,
+            hung: false,
+            stale: false,
+        });
+        self.request(index);
+        Ok(index)
+    }
+
+    fn next_worker(&mut self) -> u64 {
+        self.workers += 1;
+        self.workers
+    }
+
+    fn spawn(
+        &self,
+        index: usize,
+        worker: u64,
+        mut component: Box<dyn Component>,
+        configured: Option<Duration>,
+    ) -> Result<Sender<()>, Error> {
+        let (requests, pending) = channel::<()>();
+        let updates = self.updates.clone();
+
+        std::thread::Builder::new()
+            .name(format!("update {}", component))
+            .spawn(move || {
+                while pending.recv().is_ok() {
+                    println!("Updating {}", component);
+                    if let Err(e) = component.update() {
+                        println!("{}", e);
+                    }
+
+                    let update = Update {
+                        index,
+                        worker,
+                        snapshot: component.snapshot(),
+                        interval: component.update_interval(configured),
+                    };
+                    if updates.send(update).is_err() {
+                        break;
+                    }
+                }
+            })?;
+
+        Ok(requests)
+    }
+
+    /// Asks the component at `index` to update, unless it is still busy with
+    /// the previous update.
+    pub fn request(&mut self, index: usize) {
+        let slot = &mut self.slots[index];
+        if slot.requested.is_some() {
+            return;
+        }
+
+        if slot.requests.send(()).is_ok() {
+            slot.requested = Some(Instant::now());
+        }
+    }
+
+    /// Blocks until all requested updates have finished, or for at most the
+    /// timeout. Used to have data for the first frame.
+    pub fn wait(&mut self) {
+        let deadline = Instant::now() + self.timeout;
+        while self.slots.iter().any(|slot| slot.requested.is_some()) {
+            let left = deadline.saturating_duration_since(Instant::now());
+            match self.results.recv_timeout(left) {
+                Ok(update) => self.apply(update),
+                Err(_) => break,
+            }
+        }
+    }
+
+    fn apply(&mut self, update: Update) {
+        let slot = &mut self.slots[update.index];
+        if update.worker != slot.worker {
+            // the abandoned worker got unstuck and exits
+            slot.hung = false;
+            return;
+        }
+
+        slot.snapshot = update.snapshot;
+        slot.requested = None;
+        slot.stale = false;
+        self.finished.push((update.index, update.interval));
+    }
+
+    /// Takes the snapshots of finished updates and abandons the workers of
+    /// components whose update takes too long. Returns the components that
+    /// are done with the interval until they want to be updated again.
+    pub fn collect(&mut self) -> Vec<(usize, Option<Duration>)> {
+        while let Ok(update) = self.results.try_recv() {
+            self.apply(update);
+        }
+
+        for index in 0..self.slots.len() {
+            let slot = &self.slots[index];
+            let timed_out = slot
+                .requested
+                .is_some_and(|requested| requested.elapsed() > self.timeout);
+            if !timed_out {
+                continue;
+            }
+
+            self.slots[index].stale = true;
+            // keep at most one stuck thread around for each component
+            if !self.slots[index].hung {
+                if let Err(e) = self.replace_worker(index) {
+                    println!("Could not restart update thread: {}", e);
+                }
+            }
+        }
+
+        std::mem::take(&mut self.finished)
+    }
+
+    /// Leaves the hanging worker at `index` behind and starts a new one from
+    /// the latest snapshot, scheduled like after a finished update
+    fn replace_worker(&mut self, index: usize) -> Result<(), Error> {
+        let worker = self.next_worker();
+        let slot = &self.slots[index];
+        let component = slot.snapshot.snapshot();
+        let interval = component.update_interval(slot.configured);
+        println!("Update of {} timed out", component);
+        let requests = self.spawn(index, worker, component, slot.configured)?;
+
+        let slot = &mut self.slots[index];
+        slot.requests = requests;
+        slot.worker = worker;
+        slot.requested = None;
+        slot.hung = true;
+        self.finished.push((index, interval));
+        Ok(())
+    }
+
+    /// The latest snapshot of each component and whether it is stale
+    pub fn components(&self) -> impl Iterator<Item = (&dyn Component, bool)> {
+        self.slots
+            .iter()
+            .map(|slot| (slot.snapshot.as_ref(), slot.stale))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Drawer;
+    use embedded_graphics::prelude::Point;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    #[derive(Clone, Debug)]
+    struct Slow {
+        delay: Duration,
+        updates: u32,
+    }
+
+    impl std::fmt::Display for Slow {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "Slow {}", self.updates)
+        }
+    }
+
+    impl Component for Slow {
+        fn update_interval(&self, _configured: Option<Duration>) -> Option<Duration> {
+            Some(Duration::from_secs(60))
+        }
+
+        fn update(&mut self) -> Result<(), Error> {
+            std::thread::sleep(self.delay);
+            self.updates += 1;
+            Ok(())
+        }
+
+        fn draw(&self, _drawable: &mut Drawer, _offset: Point, _tick: u64) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    /// Hangs in the first update of all its copies, like a statfs on an
+    /// NFS mount that comes back later
+    #[derive(Clone, Debug)]
+    struct Hanging {
+        calls: Arc<AtomicU32>,
+        updates: u32,
+    }
+
+    impl std::fmt::Display for Hanging {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "Hanging {}", self.updates)
+        }
+    }
+
+    impl Component for Hanging {
+        fn update_interval(&self, _configured: Option<Duration>) -> Option<Duration> {
+            Some(Duration::from_secs(60))
+        }
+
+        fn update(&mut self) -> Result<(), Error> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst);
+            if call == 0 {
+                std::thread::sleep(Duration::from_millis(300));
+            }
+            self.updates = call + 1;
+            Ok(())
+        }
+
+        fn draw(&self, _drawable: &mut Drawer, _offset: Point, _tick: u64) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    fn wait_for_update(collector: &mut Collector) -> Vec<(usize, Option<Duration>)> {
+        loop {
+            let finished = collector.collect();
+            if !finished.is_empty() {
+                return finished;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn draws_snapshot_of_finished_update() {
+        let mut collector = Collector::new(Duration::from_secs(10));
+        let index = collector
+            .add(
+                Box::new(Slow {
+                    delay: Duration::ZERO,
+                    updates: 0,
+                }),
+                None,
+            )
+            .unwrap();
+
+        assert_eq!(
+            wait_for_update(&mut collector),
+            vec![(index, Some(Duration::from_secs(60)))]
+        );
+        let (component, stale) = collector.components().next().unwrap();
+        assert_eq!(component.to_string(), "Slow 1");
+        assert!(!stale);
+    }
+
+    #[test]
+    fn waits_for_first_updates() {
+        let mut collector = Collector::new(Duration::from_secs(10));
+        let index = collector
+            .add(
+                Box::new(Slow {
+                    delay: Duration::from_millis(50),
+                    updates: 0,
+                }),
+                None,
+            )
+            .unwrap();
+        collector.wait();
+
+        let (component, _) = collector.components().next().unwrap();
+        assert_eq!(component.to_string(), "Slow 1");
+        assert_eq!(
+            collector.collect(),
+            vec![(index, Some(Duration::from_secs(60)))]
+        );
+    }
+
+    #[test]
+    fn gives_up_waiting_after_timeout() {
+        let mut collector = Collector::new(Duration::from_millis(50));
+        collector
+            .add(
+                Box::new(Slow {
+                    delay: Duration::from_millis(500),
+                    updates: 0,
+                }),
+                None,
+            )
+            .unwrap();
+
+        let started = Instant::now();
+        collector.wait();
+        assert!(started.elapsed() < Duration::from_millis(400));
+        let (component, _) = collector.components().next().unwrap();
+        assert_eq!(component.to_string(), "Slow 0");
+    }
+
+    #[test]
+    fn replaces_workers_of_hung_updates() {
+        let mut collector = Collector::new(Duration::from_millis(50));
+        let index = collector
+            .add(
+                Box::new(Hanging {
+                    calls: Arc::new(AtomicU32::new(0)),
+                    updates: 0,
+                }),
+                None,
+            )
+            .unwrap();
+        std::thread::sleep(Duration::from_millis(100));
+
+        // the previous snapshot is still drawn while the update hangs, and
+        // the component is scheduled again on a new worker
+        assert_eq!(
+            collector.collect(),
+            vec![(index, Some(Duration::from_secs(60)))]
+        );
+        let (component, stale) = collector.components().next().unwrap();
+        assert_eq!(component.to_string(), "Hanging 0");
+        assert!(stale);
+
+        collector.request(index);
+        wait_for_update(&mut collector);
+        let (component, stale) = collector.components().next().unwrap();
+        assert_eq!(component.to_string(), "Hanging 2");
+        assert!(!stale);
+
+        // the late result of the abandoned worker is dropped
+        std::thread::sleep(Duration::from_millis(300));
+        assert!(collector.collect().is_empty());
+        let (component, _) = collector.components().next().unwrap();
+        assert_eq!(component.to_string(), "Hanging 2");
+    }
+}
@@ -0,0 +1,265 @@
+use super::network::find_interface_path;
+use super::text::draw_text;
+use super::{Component, Drawer, Error};
+
+use embedded_graphics::{prelude::*, text::Alignment};
+
+use std::{
+    fs,
+    net::{IpAddr, Ipv6Addr},
+    path::{Path, PathBuf},
+};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct LinkState {
+    operstate: String,
+    speed_mbits: Option<u32>,
+}
+
+/// IP addresses and link state of a network adapter
+#[derive(Clone, Debug)]
+pub struct Address {
+    name: String,
+    interface_path: PathBuf,
+    include_ipv6: bool,
+    link: Option<LinkState>,
+    addresses: Vec<IpAddr>,
+}
+
+impl Address {
+    pub fn new(name: String, sysfs_root: &Path, include_ipv6: bool) -> Result<Self, Error> {
+        let interface_path = find_interface_path(sysfs_root, &name)?;
+
+        Ok(Self {
+            name,
+            interface_path,
+            include_ipv6,
+            link: None,
+            addresses: Vec::new(),
+        })
+    }
+
+    fn interface_name(&self) -> String {
+        // the sysfs directory is named after the kernel's interface name, even
+        // when configured with an `ip link` style name like eth0@if3
+        self.interface_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.name.clone())
+    }
+}
+
+impl std::fmt::Display for Address {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Address {}", self.name)
+    }
+}
+
+impl Component for Address {
+    fn update_interval(
+        &self,
+        configured: Option<std::time::Duration>,
+    ) -> Option<std::time::Duration> {
+        // pick up new DHCP leases quickly
+        Some(configured.unwrap_or(std::time::Duration::from_secs(5)))
+    }
+
+    fn update(&mut self) -> Result<(), Error> {
+        self.link = Some(read_link_state(&self.interface_path)?);
+
+        self.addresses = interface_addresses(&self.interface_name(), self.include_ipv6)?;
+
+        Ok(())
+    }
+
+    fn height(&self, line_height: u32) -> u32 {
+        2 * line_height
+    }
+
+    fn draw(&self, drawable: &mut Drawer, offset: Point, tick: u64) -> Result<(), Error> {
+        let link = match &self.link {
+            Some(link) => match link.speed_mbits {
+                Some(speed) => format!("{} {} {}", self.name, link.operstate, format_speed(speed)),
+                None => format!("{} {}", self.name, link.operstate),
+            },
+            None => format!("{} -", self.name),
+        };
+        draw_text(
+            drawable,
+            &link,
+            offset,
+            Drawer::WIDTH as u32 + 1,
+            Alignment::Left,
+            tick,
+        )?;
+
+        let addresses = match self.addresses.is_empty() {
+            true => "no address".to_string(),
+            false => self
+                .addresses
+                .iter()
+                .map(|address| address.to_string())
+                .collect::<Vec<_>>()
+                .join(" "),
+        };
+        draw_text(
+            drawable,
+            &addresses,
+            offset + Point::new(0, drawable.line_height() as i32),
+            Drawer::WIDTH as u32 + 1,
+            Alignment::Left,
+            tick,
+        )
+    }
+}
+
+fn read_link_state(interface_path: &Path) -> Result<LinkState, Error> {
+    let operstate = fs::read_to_string(interface_path.join("operstate"))?
+        .trim()
+        .to_string();
+    // reading the speed fails or reports -1 for links that are down and for
+    // virtual adapters
+    let speed_mbits = fs::read_to_string(interface_path.join("speed"))
+        .ok()
+        .and_then(|speed| speed.trim().parse::<i64>().ok())
+        .and_then(|speed| u32::try_from(speed).ok())
+        .filter(|speed| *speed > 0);
+
+    Ok(LinkState {
+        operstate,
+        speed_mbits,
+    })
+}
+
+/// Addresses assigned to an interface, IPv4 addresses first.
+pub(super) fn interface_addresses(
+    interface_name: &str,
+    include_ipv6: bool,
+) -> Result<Vec<IpAddr>, Error> {
+    let addresses = nix::ifaddrs::getifaddrs()?.filter_map(|interface| {
+        let address = interface.address?;
+        let ip = match (address.as_sockaddr_in(), address.as_sockaddr_in6()) {
+            (Some(v4), _) => IpAddr::V4(v4.ip()),
+            (_, Some(v6)) => IpAddr::V6(v6.ip()),
+            _ => return None,
+        };
+        Some((interface.interface_name, ip))
+    });
+
+    Ok(select_addresses(addresses, interface_name, include_ipv6))
+}
+
+fn select_addresses(
+    addresses: impl Iterator<Item = (String, IpAddr)>,
+    interface_name: &str,
+    include_ipv6: bool,
+) -> Vec<IpAddr> {
+    let mut selected: Vec<IpAddr> = addresses
+        .filter(|(name, _)| name == interface_name)
+        .map(|(_, address)| address)
+        .filter(|address| match address {
+            IpAddr::V4(_) => true,
+            IpAddr::V6(v6) => include_ipv6 && is_global_ipv6(v6),
+        })
+        .collect();
+    // IPv4 first, they are shorter and more likely to be what's needed
+    selected.sort_by_key(|address| address.is_ipv6());
+    selected.dedup();
+    selected
+}
+
+fn is_global_ipv6(address: &Ipv6Addr) -> bool {
+    let first_segment = address.segments()[0];
+    let link_local = first_segment & 0xffc0 == 0xfe80;
+    let unique_local = first_segment & 0xfe00 == 0xfc00;
+
+    !(address.is_loopback()
+        || address.is_unspecified()
+        || address.is_multicast()
+        || link_local
+        || unique_local)
+}
+
+fn format_speed(speed_mbits: u32) -> String {
+    match speed_mbits {
+        speed if speed >= 1000 && speed % 1000 == 0 => format!("{}G", speed / 1000),
+        speed if speed >= 1000 => format!("{:.1}G", speed as f32 / 1000.0),
+        speed => format!("{}M", speed),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::net::Ipv4Addr;
+
+    fn fixture() -> Vec<(String, IpAddr)> {
+        vec![
+            ("lo".to_string(), IpAddr::V4(Ipv4Addr::LOCALHOST)),
+            ("eth0".to_string(), IpAddr::V6("fe80::1".parse().unwrap())),
+            (
+                "eth0".to_string(),
+                IpAddr::V6("2001:db8::2".parse().unwrap()),
+            ),
+            ("eth0".to_string(), IpAddr::V6("fd00::3".parse().unwrap())),
+            (
+                "eth0".to_string(),
+                IpAddr::V4(Ipv4Addr::new(192, 168, 1, 23)),
+            ),
+        ]
+    }
+
+    #[test]
+    fn selects_ipv4_of_interface() {
+        assert_eq!(
+            select_addresses(fixture().into_iter(), "eth0", false),
+            vec![IpAddr::V4(Ipv4Addr::new(192, 168, 1, 23))]
+        );
+    }
+
+    #[test]
+    fn selects_global_ipv6_after_ipv4() {
+        assert_eq!(
+            select_addresses(fixture().into_iter(), "eth0", true),
+            vec![
+                IpAddr::V4(Ipv4Addr::new(192, 168, 1, 23)),
+                IpAddr::V6("2001:db8::2".parse().unwrap()),
+            ]
+        );
+    }
+
+    #[test]
+    fn formats_link_speeds() {
+        assert_eq!(format_speed(100), "100M");
+        assert_eq!(format_speed(1000), "1G");
+        assert_eq!(format_speed(2500), "2.5G");
+    }
+
+    #[test]
+    fn reads_link_state_from_sysfs() {
+        let root = std::env::temp_dir().join(format!("oled-address-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("eth0")).unwrap();
+        fs::create_dir_all(root.join("wg0")).unwrap();
+        fs::write(root.join("eth0").join("operstate"), "up\n").unwrap();
+        fs::write(root.join("eth0").join("speed"), "1000\n").unwrap();
+        fs::write(root.join("wg0").join("operstate"), "unknown\n").unwrap();
+        fs::write(root.join("wg0").join("speed"), "-1\n").unwrap();
+
+        assert_eq!(
+            read_link_state(&root.join("eth0")).unwrap(),
+            LinkState {
+                operstate: "up".to_string(),
+                speed_mbits: Some(1000),
+            }
+        );
+        assert_eq!(
+            read_link_state(&root.join("wg0")).unwrap(),
+            LinkState {
+                operstate: "unknown".to_string(),
+                speed_mbits: None,
+            }
+        );
+    }
+}
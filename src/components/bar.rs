@@ -0,0 +1,39 @@
+use super::{Drawer, Error};
+
+use embedded_graphics::{prelude::*, primitives::Line};
+
+/// Draws a horizontal bar from `offset` to `width` pixels to the right of it,
+/// filled to `fraction` and vertically centered in the line.
+pub fn draw_bar(
+    drawable: &mut Drawer,
+    offset: Point,
+    width: i32,
+    fraction: f32,
+) -> Result<(), Error> {
+    let line_height = drawable.line_height() as i32;
+    let middle = (line_height - 1) / 2;
+    // the end marks are at most 7 pixels high and keep away from other lines
+    let top = (middle - 3).max(2.min(middle));
+    let bottom = (middle + 3).min((line_height - 3).max(middle));
+    let filled_width = (width as f32 * fraction.clamp(0.0, 1.0)) as i32;
+
+    Line::new(
+        Point::new(0, middle) + offset,
+        Point::new(filled_width, middle) + offset,
+    )
+    .into_styled(drawable.base_primitive_style)
+    .draw(&mut drawable.display)?;
+
+    Line::new(Point::new(0, top) + offset, Point::new(0, bottom) + offset)
+        .into_styled(drawable.base_primitive_style)
+        .draw(&mut drawable.display)?;
+
+    Line::new(
+        Point::new(width, top) + offset,
+        Point::new(width, bottom) + offset,
+    )
+    .into_styled(drawable.base_primitive_style)
+    .draw(&mut drawable.display)?;
+
+    Ok(())
+}
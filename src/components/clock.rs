@@ -0,0 +1,184 @@
+use super::text::draw_text;
+use super::{Component, Drawer, Error};
+
+use embedded_graphics::{
+    mono_font::ascii::FONT_10X20, prelude::*, primitives::Line, text::Alignment,
+};
+
+use std::{ffi::CString, path::Path};
+
+extern "C" {
+    // not exposed by the libc crate
+    fn tzset();
+}
+
+/// Local time and date
+#[derive(Clone, Debug)]
+pub struct Clock {
+    format: CString,
+    large: bool,
+    display_string: String,
+    synchronized: bool,
+}
+
+impl Clock {
+    /// `format` uses strftime(3) conversions. The time zone is read from
+    /// `localtime`, usually /etc/localtime of the host.
+    pub fn new(format: &str, localtime: &Path, large: bool) -> Result<Self, Error> {
+        if !localtime.is_file() {
+            return Err(format!("Could not find time zone file {}", localtime.display()).into());
+        }
+
+        // glibc reads zoneinfo files given as TZ=:<path>. This has to happen
+        // while there are no other threads that could read the environment.
+        std::env::set_var("TZ", format!(":{}", localtime.display()));
+        unsafe { tzset() };
+
+        Ok(Self {
+            format: CString::new(format).map_err(|_| "clock format must not contain NUL")?,
+            large,
+            display_string: "".to_string(),
+            synchronized: true,
+        })
+    }
+}
+
+impl std::fmt::Display for Clock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Clock")
+    }
+}
+
+impl Component for Clock {
+    fn update_interval(
+        &self,
+        configured: Option<std::time::Duration>,
+    ) -> Option<std::time::Duration> {
+        // the format might contain seconds
+        Some(configured.unwrap_or(std::time::Duration::from_secs(1)))
+    }
+
+    fn update(&mut self) -> Result<(), Error> {
+        let now = unsafe { libc::time(std::ptr::null_mut()) };
+        let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+        if unsafe { libc::localtime_r(&now, &mut tm) }.is_null() {
+            return Err("Could not convert time to local time".into());
+        }
+
+        self.display_string = format_time(&tm, &self.format)?;
+        self.synchronized = clock_synchronized()?;
+        Ok(())
+    }
+
+    fn height(&self, line_height: u32) -> u32 {
+        match self.large {
+            true => FONT_10X20.character_size.height + 1,
+            false => line_height,
+        }
+    }
+
+    fn draw(&self, drawable: &mut Drawer, offset: Point, tick: u64) -> Result<(), Error> {
+        let base_text_style = drawable.base_text_style;
+        if self.large {
+            drawable.base_text_style.font = &FONT_10X20;
+        }
+        let result = draw_text(
+            drawable,
+            &self.display_string,
+            offset,
+            Drawer::WIDTH as u32 + 1,
+            Alignment::Left,
+            tick,
+        );
+        drawable.base_text_style = base_text_style;
+        result?;
+
+        if !self.synchronized {
+            // a small cross in the top right corner
+            let corner = offset + Point::new(Drawer::WIDTH as i32 - 2, 0);
+            Line::new(corner, corner + Point::new(2, 2))
+                .into_styled(drawable.base_primitive_style)
+                .draw(&mut drawable.display)?;
+            Line::new(corner + Point::new(0, 2), corner + Point::new(2, 0))
+                .into_styled(drawable.base_primitive_style)
+                .draw(&mut drawable.display)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn format_time(tm: &libc::tm, format: &CString) -> Result<String, Error> {
+    let mut buffer = [0u8; 64];
+    let length = unsafe {
+        libc::strftime(
+            buffer.as_mut_ptr() as *mut libc::c_char,
+            buffer.len(),
+            format.as_ptr(),
+            tm,
+        )
+    };
+
+    // strftime returns 0 both when the result doesn't fit and when it is
+    // legitimately empty, an empty clock is useless either way
+    if length == 0 {
+        return Err("Could not format time, is the clock format too long?".into());
+    }
+
+    Ok(String::from_utf8_lossy(&buffer[..length]).to_string())
+}
+
+fn clock_synchronized() -> Result<bool, Error> {
+    let mut timex: libc::timex = unsafe { std::mem::zeroed() }; // modes 0 only reads
+    let state = unsafe { libc::adjtimex(&mut timex) };
+    if state == -1 {
+        return Err(nix::errno::Errno::last().into());
+    }
+
+    Ok(is_synchronized(state, timex.status))
+}
+
+fn is_synchronized(state: libc::c_int, status: libc::c_int) -> bool {
+    state != libc::TIME_ERROR && status & libc::STA_UNSYNC == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tm() -> libc::tm {
+        let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+        tm.tm_year = 2024 - 1900;
+        tm.tm_mon = 2;
+        tm.tm_mday = 9;
+        tm.tm_wday = 6;
+        tm.tm_hour = 7;
+        tm.tm_min = 5;
+        tm.tm_sec = 3;
+        tm
+    }
+
+    #[test]
+    fn formats_with_strftime_conversions() {
+        assert_eq!(
+            format_time(&tm(), &CString::new("%H:%M:%S").unwrap()).unwrap(),
+            "07:05:03"
+        );
+        assert_eq!(
+            format_time(&tm(), &CString::new("%d.%m.%Y").unwrap()).unwrap(),
+            "09.03.2024"
+        );
+    }
+
+    #[test]
+    fn empty_format_is_an_error() {
+        assert!(format_time(&tm(), &CString::new("").unwrap()).is_err());
+    }
+
+    #[test]
+    fn unsynchronized_clock_is_detected() {
+        assert!(is_synchronized(libc::TIME_OK, 0));
+        assert!(!is_synchronized(libc::TIME_ERROR, 0));
+        assert!(!is_synchronized(libc::TIME_OK, libc::STA_UNSYNC));
+    }
+}
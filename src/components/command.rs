@@ -0,0 +1,437 @@
+use std::io::Read;
+use std::process::{Child, Stdio};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime};
+
+use super::bar::draw_bar;
+use super::graph::{compact_number, draw_graph, GraphMode, Scale};
+use super::text::draw_text;
+use super::{Component, Drawer, Error, History, Snapshot};
+
+use embedded_graphics::{prelude::*, text::Alignment};
+
+/// How the output of a command is displayed
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CommandOutput {
+    /// First line of stdout as text
+    Text,
+    /// A number, graphed over time
+    Number,
+    /// A fraction between 0 and 1, drawn as a bar
+    Fraction,
+}
+
+/// A command line as given on the command line, `MODE:INTERVAL:COMMAND`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CommandSpec {
+    pub output: CommandOutput,
+    pub interval: Duration,
+    pub command_line: String,
+}
+
+impl std::str::FromStr for CommandSpec {
+    type Err = Error;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let mut parts = spec.splitn(3, ':');
+        let (Some(output), Some(interval), Some(command_line)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(format!("'{}' is not in the format MODE:INTERVAL:COMMAND", spec).into());
+        };
+
+        let output = match output {
+            "text" => CommandOutput::Text,
+            "number" => CommandOutput::Number,
+            "fraction" => CommandOutput::Fraction,
+            unknown => {
+                return Err(format!(
+                    "{} is not a known command output, use text, number or fraction",
+                    unknown
+                )
+                .into())
+            }
+        };
+        let interval = interval
+            .parse()
+            .map_err(|_| format!("{} is not a valid interval in seconds", interval))?;
+
+        if command_line.trim().is_empty() {
+            return Err("command must not be empty".into());
+        }
+
+        Ok(Self {
+            output,
+            interval: Duration::from_secs(interval),
+            command_line: command_line.to_string(),
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Value {
+    Text(String),
+    Number(f32),
+    Fraction(f32),
+}
+
+/// A started command and the thread reading its stdout, so commands with
+/// more output than fits into the pipe don't block
+#[derive(Debug)]
+struct Running {
+    child: Child,
+    started: Instant,
+    stdout: JoinHandle<std::io::Result<String>>,
+}
+
+/// Periodically runs a shell command and displays its output
+#[derive(Debug)]
+pub struct Command {
+    spec: CommandSpec,
+    timeout: Duration,
+    running: Option<Running>,
+    failed: bool,
+    text: Option<String>,
+    fraction: Option<f32>,
+    history: History,
+}
+
+impl Command {
+    pub fn new(spec: CommandSpec, timeout: Duration) -> Self {
+        Self {
+            spec,
+            timeout,
+            running: None,
+            failed: false,
+            text: None,
+            fraction: None,
+            history: History::new(Drawer::WIDTH.into()),
+        }
+    }
+
+    fn start(&mut self) -> Result<(), Error> {
+        let mut child = std::process::Command::new("sh")
+            .arg("-c")
+            .arg(&self.spec.command_line)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let mut pipe = child.stdout.take().ok_or("Could not capture stdout")?;
+        let stdout = std::thread::Builder::new()
+            .name(format!("stdout {}", self.spec.command_line))
+            .spawn(move || {
+                let mut stdout = String::new();
+                pipe.read_to_string(&mut stdout).map(|_| stdout)
+            })?;
+
+        self.running = Some(Running {
+            child,
+            started: Instant::now(),
+            stdout,
+        });
+        Ok(())
+    }
+
+    fn collect(&mut self) -> Result<(), Error> {
+        let Some(running) = &mut self.running else {
+            return Err("No command running to collect".into());
+        };
+
+        // done once the shell exited and nothing holds its stdout open
+        // anymore, like a child it left running in the background
+        let status = match running.child.try_wait()? {
+            Some(status) if running.stdout.is_finished() => status,
+            _ if running.started.elapsed() > self.timeout => {
+                let _ = running.child.kill();
+                let _ = running.child.wait();
+                // the reader ends by itself once everything holding the pipe
+                // is gone
+                self.running = None;
+                return Err(format!("'{}' timed out", self.spec.command_line).into());
+            }
+            _ => return Ok(()), // still running
+        };
+
+        let Some(running) = self.running.take() else {
+            return Ok(());
+        };
+        let stdout = running
+            .stdout
+            .join()
+            .map_err(|_| "Could not read command output")??;
+
+        if !status.success() {
+            return Err(format!("'{}' failed: {}", self.spec.command_line, status).into());
+        }
+
+        match parse_output(self.spec.output, &stdout)? {
+            Value::Text(text) => self.text = Some(text),
+            Value::Fraction(fraction) => self.fraction = Some(fraction),
+            Value::Number(number) => self.history.push(SystemTime::now(), number),
+        }
+
+        Ok(())
+    }
+
+    fn draw_graph(&self, drawable: &mut Drawer, offset: Point) -> Result<(), Error> {
+        let size = Size::new(Drawer::WIDTH as u32 + 1, drawable.line_height());
+        draw_graph(
+            drawable,
+            offset,
+            size,
+            &self.history,
+            GraphMode::Line,
+            Scale::Auto(compact_number),
+        )
+    }
+
+    fn draw_bar(&self, drawable: &mut Drawer, offset: Point) -> Result<(), Error> {
+        draw_bar(
+            drawable,
+            offset,
+            Drawer::WIDTH.into(),
+            self.fraction.unwrap_or(0.0),
+        )
+    }
+}
+
+impl std::fmt::Display for Command {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Command '{}'", self.spec.command_line)
+    }
+}
+
+impl Snapshot for Command {
+    fn snapshot(&self) -> Box<dyn Component> {
+        // the running child belongs to the original
+        Box::new(Self {
+            spec: self.spec.clone(),
+            timeout: self.timeout,
+            running: None,
+            failed: self.failed,
+            text: self.text.clone(),
+            fraction: self.fraction,
+            history: self.history.clone(),
+        })
+    }
+}
+
+impl Component for Command {
+    fn update_interval(&self, configured: Option<Duration>) -> Option<Duration> {
+        match self.running {
+            // check on the running command every time we get the chance
+            Some(_) => Some(Duration::ZERO),
+            None => Some(configured.unwrap_or(self.spec.interval)),
+        }
+    }
+
+    fn update(&mut self) -> Result<(), Error> {
+        if self.running.is_none() {
+            return self.start();
+        }
+
+        let result = self.collect();
+        if self.running.is_none() {
+            self.failed = result.is_err();
+        }
+        result
+    }
+
+    fn history(&self) -> Option<&History> {
+        Some(&self.history)
+    }
+
+    fn history_mut(&mut self) -> Option<&mut History> {
+        Some(&mut self.history)
+    }
+
+    fn draw(&self, drawable: &mut Drawer, offset: Point, tick: u64) -> Result<(), Error> {
+        match self.spec.output {
+            CommandOutput::Text => {
+                let font = drawable.base_text_style.font;
+                let character_width = font.character_size.width + font.character_spacing;
+                let width = match self.failed {
+                    // leave room for the error marker and a pixel of space
+                    true => Drawer::WIDTH as u32 - character_width,
+                    false => Drawer::WIDTH as u32 + 1,
+                };
+                draw_text(
+                    drawable,
+                    self.text.as_deref().unwrap_or("-"),
+                    offset,
+                    width,
+                    Alignment::Left,
+                    tick,
+                )?;
+            }
+            CommandOutput::Number => self.draw_graph(drawable, offset)?,
+            CommandOutput::Fraction => self.draw_bar(drawable, offset)?,
+        }
+
+        if self.failed {
+            draw_text(
+                drawable,
+                "!",
+                offset,
+                Drawer::WIDTH as u32 + 1,
+                Alignment::Right,
+                tick,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_output(output: CommandOutput, stdout: &str) -> Result<Value, Error> {
+    let line = stdout.lines().next().unwrap_or("").trim();
+
+    match output {
+        CommandOutput::Text => Ok(Value::Text(line.to_string())),
+        CommandOutput::Number => Ok(Value::Number(
+            line.parse()
+                .map_err(|_| format!("'{}' is not a number", line))?,
+        )),
+        CommandOutput::Fraction => {
+            let fraction: f32 = line
+                .parse()
+                .map_err(|_| format!("'{}' is not a number", line))?;
+            if !(0.0..=1.0).contains(&fraction) {
+                return Err(format!("{} is not between 0 and 1", fraction).into());
+            }
+            Ok(Value::Fraction(fraction))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_to_completion(command: &mut Command) -> Result<(), Error> {
+        command.update()?;
+        while command.running.is_some() {
+            std::thread::sleep(Duration::from_millis(10));
+            command.update()?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn parses_spec() {
+        assert_eq!(
+            "number:60:cat /sys/class/thermal/thermal_zone0/temp"
+                .parse::<CommandSpec>()
+                .unwrap(),
+            CommandSpec {
+                output: CommandOutput::Number,
+                interval: Duration::from_secs(60),
+                command_line: "cat /sys/class/thermal/thermal_zone0/temp".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn spec_keeps_colons_in_command() {
+        assert_eq!(
+            "text:5:echo a:b"
+                .parse::<CommandSpec>()
+                .unwrap()
+                .command_line,
+            "echo a:b"
+        );
+    }
+
+    #[test]
+    fn rejects_bad_specs() {
+        assert!("text:5".parse::<CommandSpec>().is_err());
+        assert!("graph:5:true".parse::<CommandSpec>().is_err());
+        assert!("text:soon:true".parse::<CommandSpec>().is_err());
+        assert!("text:5: ".parse::<CommandSpec>().is_err());
+    }
+
+    #[test]
+    fn parses_outputs() {
+        assert_eq!(
+            parse_output(CommandOutput::Text, "backup ok\nmore\n").unwrap(),
+            Value::Text("backup ok".to_string())
+        );
+        assert_eq!(
+            parse_output(CommandOutput::Number, "42.5\n").unwrap(),
+            Value::Number(42.5)
+        );
+        assert_eq!(
+            parse_output(CommandOutput::Fraction, "0.25\n").unwrap(),
+            Value::Fraction(0.25)
+        );
+        assert!(parse_output(CommandOutput::Number, "many\n").is_err());
+        assert!(parse_output(CommandOutput::Fraction, "1.5\n").is_err());
+    }
+
+    #[test]
+    fn runs_command() {
+        let mut command = Command::new(
+            "number:60:echo 42".parse().unwrap(),
+            Duration::from_secs(10),
+        );
+        run_to_completion(&mut command).unwrap();
+
+        assert!(!command.failed);
+        assert_eq!(
+            command
+                .history
+                .columns(SystemTime::now())
+                .collect::<Vec<_>>(),
+            vec![(0, 42.0)]
+        );
+    }
+
+    #[test]
+    fn marks_failed_commands() {
+        let mut command = Command::new(
+            "text:60:echo partial; exit 3".parse().unwrap(),
+            Duration::from_secs(10),
+        );
+
+        assert!(run_to_completion(&mut command).is_err());
+        assert!(command.failed);
+        assert_eq!(command.text, None);
+    }
+
+    #[test]
+    fn reads_output_larger_than_pipe() {
+        let mut command = Command::new(
+            "text:60:echo done; head -c 200000 /dev/zero"
+                .parse()
+                .unwrap(),
+            Duration::from_secs(10),
+        );
+        run_to_completion(&mut command).unwrap();
+
+        assert!(!command.failed);
+        assert_eq!(command.text.as_deref(), Some("done"));
+    }
+
+    #[test]
+    fn times_out_background_children_holding_stdout() {
+        let mut command = Command::new(
+            "text:60:echo started; sleep 10 &".parse().unwrap(),
+            Duration::from_millis(200),
+        );
+
+        let started = Instant::now();
+        assert!(run_to_completion(&mut command).is_err());
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(command.failed);
+    }
+
+    #[test]
+    fn kills_commands_after_timeout() {
+        let mut command = Command::new("text:60:sleep 10".parse().unwrap(), Duration::ZERO);
+
+        assert!(run_to_completion(&mut command).is_err());
+        assert!(command.failed);
+        assert!(command.running.is_none());
+    }
+}
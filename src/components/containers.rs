@@ -0,0 +1,215 @@
+use super::text::draw_text;
+use super::{Component, Drawer, Error};
+use crate::http;
+
+use embedded_graphics::{prelude::*, text::Alignment};
+
+use std::{os::unix::net::UnixStream, path::PathBuf, time::Duration};
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+struct ContainerCounts {
+    running: usize,
+    stopped: usize,
+    unhealthy: usize,
+}
+
+/// Running, stopped and unhealthy containers from a Docker compatible API,
+/// like the one of Docker or Podman. Unhealthy containers make the
+/// component alert.
+#[derive(Clone, Debug)]
+pub struct Containers {
+    socket: PathBuf,
+    timeout: Duration,
+    counts: Option<ContainerCounts>,
+}
+
+impl Containers {
+    pub fn new(socket: PathBuf, timeout: Duration) -> Self {
+        Self {
+            socket,
+            timeout,
+            counts: None,
+        }
+    }
+
+    fn query(&self) -> Result<String, Error> {
+        let stream = UnixStream::connect(&self.socket)
+            .map_err(|e| format!("Could not connect to {}: {}", self.socket.display(), e))?;
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+        http::get(stream, "localhost", "/containers/json?all=1")
+    }
+}
+
+impl std::fmt::Display for Containers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Containers")
+    }
+}
+
+impl Component for Containers {
+    fn update_interval(&self, configured: Option<Duration>) -> Option<Duration> {
+        Some(configured.unwrap_or(Duration::from_secs(30)))
+    }
+
+    fn update(&mut self) -> Result<(), Error> {
+        let result = self
+            .query()
+            .and_then(|containers| parse_containers(&containers));
+        self.counts = result.as_ref().ok().copied();
+        result.map(|_| ())
+    }
+
+    fn height(&self, line_height: u32) -> u32 {
+        match self.counts {
+            Some(counts) if counts.unhealthy > 0 => 3 * line_height,
+            _ => 2 * line_height,
+        }
+    }
+
+    fn alerting(&self) -> bool {
+        self.counts.is_some_and(|counts| counts.unhealthy > 0)
+    }
+
+    fn draw(&self, drawable: &mut Drawer, offset: Point, tick: u64) -> Result<(), Error> {
+        let font = drawable.base_text_style.font;
+        let character_width = font.character_size.width + font.character_spacing;
+        let width = Drawer::WIDTH as u32 + 1;
+        let line_height = drawable.line_height() as i32;
+
+        let rows = match self.counts {
+            Some(counts) => {
+                let mut rows = vec![
+                    ("Running", counts.running.to_string()),
+                    ("Stopped", counts.stopped.to_string()),
+                ];
+                if counts.unhealthy > 0 {
+                    rows.push(("Unhealthy", counts.unhealthy.to_string()));
+                }
+                rows
+            }
+            None => vec![("Running", "!".to_string()), ("Stopped", "!".to_string())],
+        };
+
+        for (i, (label, count)) in rows.iter().enumerate() {
+            let row = offset + Point::new(0, line_height * i as i32);
+            // leave room for the count next to the label
+            let label_width = width.saturating_sub((count.len() as u32 + 1) * character_width);
+            draw_text(drawable, label, row, label_width, Alignment::Left, tick)?;
+            draw_text(drawable, count, row, width, Alignment::Right, tick)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Counts the containers listed by `GET /containers/json?all=1`. Health
+/// checks only show up in the human readable status, like
+/// `Up 2 hours (unhealthy)`.
+fn parse_containers(json: &str) -> Result<ContainerCounts, Error> {
+    let containers: serde_json::Value =
+        serde_json::from_str(json).map_err(|e| format!("Could not parse JSON: {}", e))?;
+    let containers = containers
+        .as_array()
+        .ok_or("container API did not return a list")?;
+
+    let mut counts = ContainerCounts::default();
+    for container in containers {
+        match container["State"].as_str() {
+            Some("running") => counts.running += 1,
+            _ => counts.stopped += 1,
+        }
+        if container["Status"]
+            .as_str()
+            .is_some_and(|status| status.contains("(unhealthy)"))
+        {
+            counts.unhealthy += 1;
+        }
+    }
+
+    Ok(counts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::tests::read_request;
+
+    use std::fs;
+    use std::io::Write;
+    use std::os::unix::net::UnixListener;
+
+    const CONTAINERS: &str = r#"[
+        {"Names": ["/samba"], "State": "running", "Status": "Up 3 days"},
+        {"Names": ["/restic"], "State": "running", "Status": "Up 2 hours (unhealthy)"},
+        {"Names": ["/nfs"], "State": "running", "Status": "Up 3 days (healthy)"},
+        {"Names": ["/migrate"], "State": "exited", "Status": "Exited (0) 3 days ago"},
+        {"Names": ["/new"], "State": "created", "Status": "Created"}
+    ]"#;
+
+    fn tempfile_dir(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!(
+            "oled-containers-test-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir(&root).unwrap();
+        root
+    }
+
+    /// Serves one request on a Unix socket like the Docker daemon would,
+    /// returning the socket's path
+    fn docker(name: &str, status: &'static str, body: &'static str) -> PathBuf {
+        let socket = tempfile_dir(name).join("docker.sock");
+        let listener = UnixListener::bind(&socket).unwrap();
+
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let request = read_request(&mut stream);
+            let response = match request.starts_with("GET /containers/json?all=1 ") {
+                true => format!("HTTP/1.0 {}\r\n\r\n{}", status, body),
+                false => "HTTP/1.0 404 Not Found\r\n\r\n".to_string(),
+            };
+            stream.write_all(response.as_bytes()).unwrap();
+        });
+
+        socket
+    }
+
+    #[test]
+    fn counts_containers() {
+        assert_eq!(
+            parse_containers(CONTAINERS).unwrap(),
+            ContainerCounts {
+                running: 3,
+                stopped: 2,
+                unhealthy: 1,
+            }
+        );
+        assert_eq!(parse_containers("[]").unwrap(), ContainerCounts::default());
+        assert!(parse_containers(r#"{"message": "page not found"}"#).is_err());
+    }
+
+    #[test]
+    fn queries_socket() {
+        let socket = docker("query", "200 OK", CONTAINERS);
+        let mut containers = Containers::new(socket, Duration::from_secs(5));
+
+        containers.update().unwrap();
+        assert_eq!(containers.counts.map(|counts| counts.running), Some(3));
+        assert!(containers.alerting());
+        assert_eq!(containers.height(10), 30);
+    }
+
+    #[test]
+    fn forgets_counts_when_api_fails() {
+        let socket = docker("failing", "500 Internal Server Error", "{}");
+        let mut containers = Containers::new(socket, Duration::from_secs(5));
+
+        assert!(containers.update().is_err());
+        assert_eq!(containers.counts, None);
+        assert!(!containers.alerting());
+        assert_eq!(containers.height(10), 20);
+    }
+}
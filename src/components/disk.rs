@@ -1,25 +1,29 @@
+use super::bar::draw_bar;
+use super::text::draw_text;
 use super::{Component, Drawer, Error};
 use crate::{Base, GlancableSizesWithOrdersOfMagnitude};
 
+use std::fs;
+use std::os::unix::fs::MetadataExt;
 use std::path::{Path, PathBuf};
 
-use embedded_graphics::{
-    prelude::*,
-    primitives::Line,
-    text::{Baseline, Text},
-};
+use embedded_graphics::{prelude::*, text::Alignment};
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Disk {
     name: String,
     mount_point: PathBuf,
     mounted: bool,
     size: u64,
     available: u64,
+    diskstats_path: PathBuf,
+    // device and completed reads and writes after the last refresh
+    last_io: Option<(u64, u64)>,
+    idle: bool,
 }
 
 impl Disk {
-    pub fn new_from_path(path: &Path) -> Result<Self, Error> {
+    pub fn new_from_path(path: &Path, procfs: &Path) -> Result<Self, Error> {
         Ok(Self {
             name: path
                 .file_name()
@@ -30,6 +34,9 @@ impl Disk {
             mounted: false,
             size: 0,
             available: 0,
+            diskstats_path: procfs.join("diskstats"),
+            last_io: None,
+            idle: false,
         })
     }
 
@@ -47,61 +54,84 @@ impl Disk {
         Ok(mount_point_fs_id != mount_parent_fs_id)
     }
 
-    fn draw_usage_bar(&self, drawable: &mut Drawer, offset: Point) -> Result<(), Error> {
-        let bar_width =
-            (Drawer::WIDTH as f32 * (1f32 - (self.available as f32 / self.size as f32))) as i32;
+    /// The completed reads and writes of the device the disk is mounted
+    /// from. Only looks at kernel counters, so it never wakes the drive.
+    /// `None` for filesystems without a block device, like NFS.
+    fn io_activity(&self) -> Result<Option<(u64, u64)>, Error> {
+        let device = fs::metadata(&self.mount_point)?.dev();
+        let diskstats = fs::read_to_string(&self.diskstats_path)?;
 
-        Line::new(Point::new(0, 5) + offset, Point::new(bar_width, 5) + offset)
-            .into_styled(drawable.base_primitive_style)
-            .draw(&mut drawable.display)?;
+        Ok(parse_io_count(
+            &diskstats,
+            nix::sys::stat::major(device),
+            nix::sys::stat::minor(device),
+        )
+        .map(|count| (device, count)))
+    }
+
+    /// Whether the disk saw no I/O since the last refresh. Its usage can't
+    /// have changed then, and the drive may have spun down. Whether it
+    /// actually did is only known to the drive itself.
+    fn saw_no_io(&self) -> Result<bool, Error> {
+        let io = self.io_activity()?;
+        Ok(io.is_some() && io == self.last_io)
+    }
 
-        Line::new(Point::new(0, 2) + offset, Point::new(0, 8) + offset)
-            .into_styled(drawable.base_primitive_style)
-            .draw(&mut drawable.display)?;
+    fn draw_usage_bar(&self, drawable: &mut Drawer, offset: Point, tick: u64) -> Result<(), Error> {
+        let font = drawable.base_text_style.font;
+        let marker_width = match self.idle {
+            // room for "zz" and a pixel of space
+            true => 2 * (font.character_size.width + font.character_spacing),
+            false => 0,
+        };
+        let bar_width = Drawer::WIDTH as u32 - marker_width;
 
-        Line::new(
-            Point::new(Drawer::WIDTH.into(), 2) + offset,
-            Point::new(Drawer::WIDTH.into(), 8) + offset,
-        )
-        .into_styled(drawable.base_primitive_style)
-        .draw(&mut drawable.display)?;
+        draw_bar(
+            drawable,
+            offset,
+            bar_width as i32,
+            1f32 - (self.available as f32 / self.size as f32),
+        )?;
+
+        if self.idle {
+            draw_text(
+                drawable,
+                "zz",
+                offset,
+                Drawer::WIDTH as u32 + 1,
+                Alignment::Right,
+                tick,
+            )?;
+        }
 
         let size_str = format!(
             "{}",
             GlancableSizesWithOrdersOfMagnitude::new(self.size, Base::Ten)
         );
-        let size_text = Text::with_baseline(
-            size_str.as_str(),
-            offset + Point::new(0, 0),
-            drawable.base_text_style,
-            Baseline::Top,
-        );
-        let size_text_width = size_text.bounding_box().size.width;
-        size_text
-            .translate(Point::new(
-                ((Drawer::WIDTH as u32 - size_text_width) / 2) as i32,
-                0,
-            ))
-            .draw(&mut drawable.display)?;
-
-        Ok(())
+        draw_text(
+            drawable,
+            &size_str,
+            offset,
+            bar_width + 1,
+            Alignment::Center,
+            tick,
+        )
     }
 
-    fn draw_not_mounted(&self, drawable: &mut Drawer, offset: Point) -> Result<(), Error> {
-        let text = Text::with_baseline(
+    fn draw_not_mounted(
+        &self,
+        drawable: &mut Drawer,
+        offset: Point,
+        tick: u64,
+    ) -> Result<(), Error> {
+        draw_text(
+            drawable,
             "-/-",
-            offset + Point::new(0, 0),
-            drawable.base_text_style,
-            Baseline::Top,
-        );
-        let text_width = text.bounding_box().size.width;
-        text.translate(Point::new(
-            ((Drawer::WIDTH as u32 - text_width) / 2) as i32,
-            0,
-        ))
-        .draw(&mut drawable.display)?;
-
-        Ok(())
+            offset,
+            Drawer::WIDTH as u32 + 1,
+            Alignment::Center,
+            tick,
+        )
     }
 }
 
@@ -112,15 +142,24 @@ impl std::fmt::Display for Disk {
 }
 
 impl Component for Disk {
-    fn should_update(&self, last_update: std::time::Duration) -> bool {
-        last_update
-            > std::time::Duration::from_secs(match self.mounted {
-                true => 60 * 5,
-                false => 5, // redetect disks quickly
-            })
+    fn update_interval(
+        &self,
+        configured: Option<std::time::Duration>,
+    ) -> Option<std::time::Duration> {
+        Some(match self.mounted {
+            true => configured.unwrap_or(std::time::Duration::from_secs(60 * 5)),
+            false => std::time::Duration::from_secs(5), // redetect disks quickly
+        })
     }
 
     fn update(&mut self) -> Result<(), Error> {
+        // leave idle drives alone so they can spin down, and keep the last
+        // values. Without diskstats the usage is just refreshed.
+        self.idle = self.mounted && self.saw_no_io().unwrap_or(false);
+        if self.idle {
+            return Ok(());
+        }
+
         self.mounted = self.is_mounted()?;
 
         if !self.mounted {
@@ -135,13 +174,107 @@ impl Component for Disk {
         self.available = stats.blocks_available() * stats.block_size() as u64; // available to non-root
                                                                                // self.free = stats.blocks_free() * stats.block_size() as u64; // available to root
 
+        // our own I/O doesn't count as activity
+        self.last_io = self.io_activity().unwrap_or(None);
+
         Ok(())
     }
 
-    fn draw(&self, drawable: &mut Drawer, offset: Point, _tick: u64) -> Result<(), Error> {
+    fn draw(&self, drawable: &mut Drawer, offset: Point, tick: u64) -> Result<(), Error> {
         match self.mounted {
-            true => self.draw_usage_bar(drawable, offset),
-            false => self.draw_not_mounted(drawable, offset),
+            true => self.draw_usage_bar(drawable, offset, tick),
+            false => self.draw_not_mounted(drawable, offset, tick),
         }
     }
 }
+
+/// Completed reads and writes of the device `major`:`minor` in the contents
+/// of /proc/diskstats
+fn parse_io_count(diskstats: &str, major: u64, minor: u64) -> Option<u64> {
+    diskstats.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 8 || fields[0].parse() != Ok(major) || fields[1].parse() != Ok(minor) {
+            return None;
+        }
+
+        let reads: u64 = fields[3].parse().ok()?;
+        let writes: u64 = fields[7].parse().ok()?;
+        Some(reads + writes)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DISKSTATS: &str =
+        "   8       0 sda 1520 310 98510 2109 840 1202 35616 7301 0 5930 9410 0 0 0 0 0 0
+   8       1 sda1 1482 310 96174 2076 838 1202 35616 7298 0 5900 9374 0 0 0 0 0 0
+ 253       0 dm-0 207 0 9164 120 12 0 96 4 0 124 124 0 0 0 0 0 0
+";
+
+    fn tempfile_dir(name: &str) -> PathBuf {
+        let root =
+            std::env::temp_dir().join(format!("oled-disk-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir(&root).unwrap();
+        root
+    }
+
+    fn diskstats_line(path: &Path, reads: u64) -> String {
+        let device = fs::metadata(path).unwrap().dev();
+        format!(
+            "{} {} test {} 0 0 0 0 0 0 0 0 0 0\n",
+            nix::sys::stat::major(device),
+            nix::sys::stat::minor(device),
+            reads
+        )
+    }
+
+    #[test]
+    fn parses_io_count() {
+        assert_eq!(parse_io_count(DISKSTATS, 8, 1), Some(1482 + 838));
+        assert_eq!(parse_io_count(DISKSTATS, 253, 0), Some(207 + 12));
+        assert_eq!(parse_io_count(DISKSTATS, 8, 2), None);
+    }
+
+    #[test]
+    fn disk_without_io_is_idle() {
+        let root = tempfile_dir("idle");
+        let mount_point = root.join("mnt");
+        fs::create_dir(&mount_point).unwrap();
+        fs::write(root.join("diskstats"), diskstats_line(&mount_point, 100)).unwrap();
+
+        let mut disk = Disk::new_from_path(&mount_point, &root).unwrap();
+        assert!(!disk.saw_no_io().unwrap());
+
+        disk.last_io = disk.io_activity().unwrap();
+        assert!(disk.saw_no_io().unwrap());
+
+        fs::write(root.join("diskstats"), diskstats_line(&mount_point, 101)).unwrap();
+        assert!(!disk.saw_no_io().unwrap());
+    }
+
+    #[test]
+    fn refreshes_usage_without_diskstats() {
+        let root = tempfile_dir("no-diskstats");
+        let mount_point = root.join("mnt");
+        fs::create_dir(&mount_point).unwrap();
+
+        let mut disk = Disk::new_from_path(&mount_point, &root).unwrap();
+        disk.mounted = true;
+        disk.update().unwrap();
+        assert!(!disk.idle);
+        assert_eq!(disk.last_io, None);
+    }
+
+    #[test]
+    fn disk_without_block_device_is_never_idle() {
+        let root = tempfile_dir("no-device");
+        fs::write(root.join("diskstats"), "").unwrap();
+
+        let mut disk = Disk::new_from_path(&root, &root).unwrap();
+        disk.last_io = disk.io_activity().unwrap();
+        assert!(!disk.saw_no_io().unwrap());
+    }
+}
@@ -0,0 +1,368 @@
+use super::text::draw_text;
+use super::{Drawer, Error, History};
+
+use embedded_graphics::{
+    mono_font::ascii::FONT_4X6, pixelcolor::BinaryColor, prelude::*, primitives::Line,
+    text::Alignment,
+};
+
+use std::time::SystemTime;
+
+// candidate seconds between tick marks on the time axis
+const TICK_SPACINGS: [u64; 9] = [10, 30, 60, 300, 600, 1800, 3600, 6 * 3600, 24 * 3600];
+// fewest columns between tick marks
+const MIN_TICK_COLUMNS: u64 = 8;
+
+/// How the samples of a graph are drawn
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum GraphMode {
+    /// Samples connected by lines
+    Line,
+    /// A bar from the bottom for each sample
+    Bars,
+    /// A line with the area below it shaded
+    Filled,
+}
+
+impl std::str::FromStr for GraphMode {
+    type Err = Error;
+
+    fn from_str(mode: &str) -> Result<Self, Self::Err> {
+        match mode {
+            "line" => Ok(Self::Line),
+            "bars" => Ok(Self::Bars),
+            "filled" => Ok(Self::Filled),
+            unknown => Err(format!(
+                "{} is not a known graph mode, use line, bars or filled",
+                unknown
+            )
+            .into()),
+        }
+    }
+}
+
+/// The range of values a graph shows
+#[derive(Clone, Copy, Debug)]
+pub enum Scale {
+    /// From 0 to a fixed maximum, marked by dots in the top corners
+    Fixed(f32),
+    /// From 0 to the largest visible sample, labeled with `label`
+    Auto(fn(f32) -> String),
+    /// Like `Auto`, but logarithmic so small values stay visible next to
+    /// large ones
+    Log(fn(f32) -> String),
+}
+
+impl Scale {
+    /// The largest value shown given the visible `values`
+    fn max(&self, values: impl Iterator<Item = f32>) -> f32 {
+        match self {
+            Self::Fixed(max) => *max,
+            Self::Auto(_) | Self::Log(_) => values.fold(0f32, f32::max),
+        }
+        .max(f32::MIN_POSITIVE)
+    }
+
+    /// How far up the scale `value` is, between 0 and 1
+    fn fraction(&self, value: f32, max: f32) -> f32 {
+        match self {
+            Self::Fixed(_) | Self::Auto(_) => value / max,
+            Self::Log(_) => value.max(0.0).ln_1p() / max.ln_1p(),
+        }
+        .clamp(0.0, 1.0)
+    }
+
+    /// Draws the label of automatic scales, returning how much room it
+    /// takes on the left
+    fn draw_label(&self, drawable: &mut Drawer, offset: Point, max: f32) -> Result<i32, Error> {
+        match self {
+            Self::Fixed(_) => Ok(0),
+            Self::Auto(label) | Self::Log(label) => draw_label(drawable, offset, &label(max)),
+        }
+    }
+}
+
+/// Where a graph's values start and which way they grow
+#[derive(Clone, Copy, Debug)]
+struct Axis {
+    // row of the value 0
+    baseline: i32,
+    // rows to the full scale, negative to grow downwards
+    extent: i32,
+}
+
+/// Draws `history` into the `size` area at `offset`, newest samples on the
+/// right. Samples are placed by their age, so missing samples leave gaps.
+/// The drawer may override `mode` and add threshold lines per component.
+pub fn draw_graph(
+    drawable: &mut Drawer,
+    offset: Point,
+    size: Size,
+    history: &History,
+    mode: GraphMode,
+    scale: Scale,
+) -> Result<(), Error> {
+    let now = SystemTime::now();
+    let bottom = size.height as i32 - 1;
+    let max = scale.max(history.columns(now).map(|(_, value)| value));
+    let left = scale.draw_label(drawable, offset, max)?;
+    let axis = Axis {
+        baseline: bottom,
+        extent: bottom,
+    };
+
+    plot(
+        drawable,
+        offset,
+        size.width,
+        left,
+        history,
+        mode,
+        axis,
+        |v| scale.fraction(v, max),
+    )?;
+
+    let color = stroke_color(drawable);
+    for threshold in drawable.graph_thresholds.clone() {
+        if threshold > max {
+            continue;
+        }
+        let y = bottom - (bottom as f32 * scale.fraction(threshold, max)) as i32;
+        drawable.display.draw_iter(
+            (left..size.width as i32)
+                .step_by(3)
+                .map(|x| Pixel(Point::new(x, y) + offset, color)),
+        )?;
+    }
+
+    draw_ticks(drawable, offset, size.width, left, bottom, history)?;
+
+    if let Scale::Fixed(_) = scale {
+        drawable.display.draw_iter(
+            [0, size.width as i32 - 1]
+                .into_iter()
+                .map(|x| Pixel(Point::new(x, 0) + offset, color)),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Draws `upper` growing up from a center line and `lower` growing down from
+/// it on the same scale, like received and sent traffic.
+pub fn draw_mirrored_graph(
+    drawable: &mut Drawer,
+    offset: Point,
+    size: Size,
+    upper: &History,
+    lower: &History,
+    mode: GraphMode,
+    scale: Scale,
+) -> Result<(), Error> {
+    let now = SystemTime::now();
+    let center = (size.height as i32 - 1) / 2;
+    let max = scale.max(
+        upper
+            .columns(now)
+            .chain(lower.columns(now))
+            .map(|(_, value)| value),
+    );
+    let left = scale.draw_label(drawable, offset, max)?;
+    let fraction = |v| scale.fraction(v, max);
+
+    let upper_axis = Axis {
+        baseline: center - 1,
+        extent: center - 1,
+    };
+    plot(
+        drawable, offset, size.width, left, upper, mode, upper_axis, fraction,
+    )?;
+
+    let lower_axis = Axis {
+        baseline: center + 1,
+        extent: -(size.height as i32 - 1 - (center + 1)),
+    };
+    plot(
+        drawable, offset, size.width, left, lower, mode, lower_axis, fraction,
+    )?;
+
+    draw_ticks(drawable, offset, size.width, left, center, upper)
+}
+
+/// Plots the samples of `history` along `axis`, right of the `left` column
+#[allow(clippy::too_many_arguments)]
+fn plot(
+    drawable: &mut Drawer,
+    offset: Point,
+    width: u32,
+    left: i32,
+    history: &History,
+    mode: GraphMode,
+    axis: Axis,
+    fraction: impl Fn(f32) -> f32,
+) -> Result<(), Error> {
+    let mode = drawable.graph_mode.unwrap_or(mode);
+    let color = stroke_color(drawable);
+    let x = |column: usize| width as i32 - 1 - column as i32;
+    let y = |value: f32| axis.baseline - (axis.extent as f32 * fraction(value)) as i32;
+
+    let mut previous: Option<(usize, f32)> = None;
+    for (column, value) in history
+        .columns(SystemTime::now())
+        .filter(|(c, _)| x(*c) >= left)
+    {
+        let point = Point::new(x(column), y(value));
+
+        match mode {
+            GraphMode::Bars => {
+                Line::new(Point::new(point.x, axis.baseline) + offset, point + offset)
+                    .into_styled(drawable.base_primitive_style)
+                    .draw(&mut drawable.display)?;
+            }
+            GraphMode::Line | GraphMode::Filled => {
+                // only connect samples in neighbouring columns, gaps stay gaps
+                let start = match previous {
+                    Some((c, v)) if c + 1 == column => Point::new(x(c), y(v)),
+                    _ => point,
+                };
+                Line::new(start + offset, point + offset)
+                    .into_styled(drawable.base_primitive_style)
+                    .draw(&mut drawable.display)?;
+            }
+        }
+
+        if mode == GraphMode::Filled {
+            // a checkerboard between the line and the baseline, so it stays
+            // distinguishable
+            let rows = match axis.extent >= 0 {
+                true => point.y + 1..=axis.baseline,
+                false => axis.baseline..=point.y - 1,
+            };
+            drawable.display.draw_iter(
+                rows.filter(|y| (point.x + y) % 2 == 0)
+                    .map(|y| Pixel(Point::new(point.x, y) + offset, color)),
+            )?;
+        }
+
+        previous = Some((column, value));
+    }
+
+    Ok(())
+}
+
+/// Tick marks on the time axis in `row`, counted from the newest column
+fn draw_ticks(
+    drawable: &mut Drawer,
+    offset: Point,
+    width: u32,
+    left: i32,
+    row: i32,
+    history: &History,
+) -> Result<(), Error> {
+    let color = stroke_color(drawable);
+    let tick_columns = tick_columns(history.step());
+
+    drawable.display.draw_iter(
+        (0..width as i32)
+            .step_by(tick_columns)
+            .map(|c| width as i32 - 1 - c)
+            .filter(|x| *x >= left)
+            .map(|x| Pixel(Point::new(x, row) + offset, color)),
+    )?;
+
+    Ok(())
+}
+
+fn stroke_color(drawable: &Drawer) -> BinaryColor {
+    drawable
+        .base_primitive_style
+        .stroke_color
+        .unwrap_or(BinaryColor::On)
+}
+
+/// Draws `label` in the top left corner in the smallest font, returning its
+/// width including a pixel of space.
+fn draw_label(drawable: &mut Drawer, offset: Point, label: &str) -> Result<i32, Error> {
+    let base_text_style = drawable.base_text_style;
+    drawable.base_text_style.font = &FONT_4X6;
+    let character_width = FONT_4X6.character_size.width + FONT_4X6.character_spacing;
+    let width = label.chars().count() as u32 * character_width;
+
+    let result = draw_text(drawable, label, offset, width, Alignment::Left, 0);
+    drawable.base_text_style = base_text_style;
+    result?;
+
+    Ok(width as i32 + 1)
+}
+
+/// Columns between tick marks for samples `step` seconds apart, a round
+/// time that leaves enough space between them
+fn tick_columns(step: Option<u64>) -> usize {
+    let Some(step) = step else {
+        return MIN_TICK_COLUMNS as usize;
+    };
+
+    TICK_SPACINGS
+        .iter()
+        .map(|spacing| spacing / step)
+        .find(|columns| *columns >= MIN_TICK_COLUMNS)
+        .unwrap_or(u64::MAX)
+        .try_into()
+        .unwrap_or(usize::MAX)
+}
+
+/// A number in at most four characters, like 4.2, 420, 42k or 4.2M
+pub fn compact_number(value: f32) -> String {
+    let (value, suffix) = match value.abs() {
+        v if v >= 1e9 => (value / 1e9, "G"),
+        v if v >= 1e6 => (value / 1e6, "M"),
+        v if v >= 1e3 => (value / 1e3, "k"),
+        _ => (value, ""),
+    };
+
+    match value.abs() < 10.0 && value.fract() != 0.0 {
+        true => format!("{:.1}{}", value, suffix),
+        false => format!("{:.0}{}", value, suffix),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_modes() {
+        assert_eq!("filled".parse::<GraphMode>().unwrap(), GraphMode::Filled);
+        assert!("pie".parse::<GraphMode>().is_err());
+    }
+
+    #[test]
+    fn ticks_fall_on_round_times() {
+        // every 10 minutes for one sample a minute
+        assert_eq!(tick_columns(Some(60)), 10);
+        // every 10 seconds for one sample a second
+        assert_eq!(tick_columns(Some(1)), 10);
+        assert_eq!(tick_columns(Some(5)), 12);
+        assert_eq!(tick_columns(None), 8);
+    }
+
+    #[test]
+    fn log_scale_keeps_small_values_visible() {
+        let log = Scale::Log(compact_number);
+        let linear = Scale::Auto(compact_number);
+
+        assert!(log.fraction(1_000.0, 1_000_000.0) > 0.4);
+        assert!(linear.fraction(1_000.0, 1_000_000.0) < 0.01);
+        assert_eq!(log.fraction(1_000_000.0, 1_000_000.0), 1.0);
+        assert_eq!(log.fraction(0.0, 1_000_000.0), 0.0);
+    }
+
+    #[test]
+    fn compacts_numbers() {
+        assert_eq!(compact_number(4.25), "4.2");
+        assert_eq!(compact_number(4.0), "4");
+        assert_eq!(compact_number(420.0), "420");
+        assert_eq!(compact_number(42_000.0), "42k");
+        assert_eq!(compact_number(4_200_000.0), "4.2M");
+    }
+}
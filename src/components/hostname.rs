@@ -1,11 +1,9 @@
+use super::text::draw_text;
 use super::{Component, Drawer, Error};
 
-use embedded_graphics::{
-    prelude::*,
-    text::{Baseline, Text},
-};
+use embedded_graphics::{prelude::*, text::Alignment};
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Hostname {
     pub hostname: Option<String>,
 }
@@ -17,8 +15,15 @@ impl std::fmt::Display for Hostname {
 }
 
 impl Component for Hostname {
-    fn should_update(&self, _last_update: std::time::Duration) -> bool {
-        self.hostname.is_none() // update when hostname not present
+    fn update_interval(
+        &self,
+        configured: Option<std::time::Duration>,
+    ) -> Option<std::time::Duration> {
+        // update only while the hostname is not present
+        match self.hostname {
+            Some(_) => None,
+            None => Some(configured.unwrap_or(std::time::Duration::from_secs(1))),
+        }
     }
 
     fn update(&mut self) -> Result<(), Error> {
@@ -28,15 +33,14 @@ impl Component for Hostname {
         Ok(())
     }
 
-    fn draw(&self, drawable: &mut Drawer, offset: Point, _tick: u64) -> Result<(), Error> {
-        Text::with_baseline(
+    fn draw(&self, drawable: &mut Drawer, offset: Point, tick: u64) -> Result<(), Error> {
+        draw_text(
+            drawable,
             self.hostname.as_ref().ok_or("hostname not available")?,
             offset,
-            drawable.base_text_style,
-            Baseline::Top,
+            Drawer::WIDTH as u32 + 1,
+            Alignment::Left,
+            tick,
         )
-        .draw(&mut drawable.display)?;
-
-        Ok(())
     }
 }
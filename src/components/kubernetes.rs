@@ -0,0 +1,317 @@
+use super::text::draw_text;
+use super::{Component, Drawer, Error};
+use crate::http;
+
+use embedded_graphics::{prelude::*, text::Alignment};
+
+use std::time::Duration;
+
+// crash looping pods listed by name, more are summed up in a line
+const MAX_CRASHING_ROWS: usize = 2;
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+struct Pods {
+    count: usize,
+    crashing: Vec<String>,
+    // the node the kubelet runs on, as named in the pods
+    node: Option<String>,
+}
+
+/// The node's Ready condition, how many pods it runs and which of them are
+/// in CrashLoopBackOff. Pods come from the kubelet's read-only API, the
+/// Ready condition from the node object of the API server.
+#[derive(Clone, Debug)]
+pub struct Kubernetes {
+    kubelet_url: String,
+    api_url: String,
+    node: Option<String>,
+    timeout: Duration,
+    ready: Option<bool>,
+    pods: Option<Pods>,
+}
+
+impl Kubernetes {
+    /// Asks the kubelet at `kubelet_url`, like `http://127.0.0.1:10255`, and
+    /// the API server at `api_url`, like a `kubectl proxy` on
+    /// `http://127.0.0.1:8001`. Without a `node` name the one of the pods is
+    /// used.
+    pub fn new(kubelet_url: &str, api_url: &str, node: Option<String>, timeout: Duration) -> Self {
+        Self {
+            kubelet_url: kubelet_url.trim_end_matches('/').to_string(),
+            api_url: api_url.trim_end_matches('/').to_string(),
+            node,
+            timeout,
+            ready: None,
+            pods: None,
+        }
+    }
+
+    fn query_ready(&self) -> Result<bool, Error> {
+        let node = self
+            .node
+            .as_ref()
+            .or_else(|| self.pods.as_ref().and_then(|pods| pods.node.as_ref()))
+            .ok_or("Node name is not known")?;
+        let url = format!("{}/api/v1/nodes/{}", self.api_url, node);
+        parse_node_ready(&http::get_url(&url, self.timeout)?)
+    }
+
+    /// The rows below the pod count, crashing pods and how many didn't fit
+    fn crashing_rows(&self) -> Vec<String> {
+        let crashing = self.pods.as_ref().map_or(&[][..], |pods| &pods.crashing);
+        match crashing.len() {
+            n if n <= MAX_CRASHING_ROWS => crashing.to_vec(),
+            n => crashing
+                .iter()
+                .take(MAX_CRASHING_ROWS - 1)
+                .cloned()
+                .chain([format!("+{} more", n - (MAX_CRASHING_ROWS - 1))])
+                .collect(),
+        }
+    }
+}
+
+impl std::fmt::Display for Kubernetes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Kubernetes")
+    }
+}
+
+impl Component for Kubernetes {
+    fn update_interval(&self, configured: Option<Duration>) -> Option<Duration> {
+        Some(configured.unwrap_or(Duration::from_secs(30)))
+    }
+
+    fn update(&mut self) -> Result<(), Error> {
+        let pods = http::get_url(&format!("{}/pods", self.kubelet_url), self.timeout)
+            .and_then(|pods| parse_pods(&pods));
+        self.pods = pods.as_ref().ok().cloned();
+
+        let ready = self.query_ready();
+        self.ready = ready.as_ref().ok().copied();
+
+        pods.and(ready).map(|_| ())
+    }
+
+    fn height(&self, line_height: u32) -> u32 {
+        line_height * (2 + self.crashing_rows().len() as u32)
+    }
+
+    fn alerting(&self) -> bool {
+        self.ready == Some(false)
+            || self
+                .pods
+                .as_ref()
+                .is_some_and(|pods| !pods.crashing.is_empty())
+    }
+
+    fn draw(&self, drawable: &mut Drawer, offset: Point, tick: u64) -> Result<(), Error> {
+        let width = Drawer::WIDTH as u32 + 1;
+        let line_height = drawable.line_height() as i32;
+
+        let ready = match self.ready {
+            Some(true) => "Ready",
+            Some(false) => "!Ready",
+            None => "-",
+        };
+        draw_text(drawable, "Node", offset, width, Alignment::Left, tick)?;
+        draw_text(drawable, ready, offset, width, Alignment::Right, tick)?;
+
+        let pods_offset = offset + Point::new(0, line_height);
+        let pods = match &self.pods {
+            Some(pods) => pods.count.to_string(),
+            None => "!".to_string(),
+        };
+        draw_text(drawable, "Pods", pods_offset, width, Alignment::Left, tick)?;
+        draw_text(drawable, &pods, pods_offset, width, Alignment::Right, tick)?;
+
+        for (i, row) in self.crashing_rows().iter().enumerate() {
+            let row_offset = offset + Point::new(0, line_height * (i as i32 + 2));
+            draw_text(drawable, row, row_offset, width, Alignment::Left, tick)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The pods in the pod list of the kubelet's `/pods` endpoint, with the
+/// names of those with a container in CrashLoopBackOff
+fn parse_pods(json: &str) -> Result<Pods, Error> {
+    let list: serde_json::Value =
+        serde_json::from_str(json).map_err(|e| format!("Could not parse JSON: {}", e))?;
+    let pods = list["items"]
+        .as_array()
+        .ok_or("kubelet did not return a pod list")?;
+
+    let crashing = pods
+        .iter()
+        .filter(|pod| {
+            ["initContainerStatuses", "containerStatuses"]
+                .iter()
+                .filter_map(|statuses| pod["status"][statuses].as_array())
+                .flatten()
+                .any(|status| status["state"]["waiting"]["reason"] == "CrashLoopBackOff")
+        })
+        .map(|pod| pod["metadata"]["name"].as_str().unwrap_or("?").to_string())
+        .collect();
+
+    Ok(Pods {
+        count: pods.len(),
+        crashing,
+        node: pods
+            .iter()
+            .find_map(|pod| pod["spec"]["nodeName"].as_str())
+            .map(str::to_string),
+    })
+}
+
+/// Whether the `Ready` condition of a node object is true
+fn parse_node_ready(json: &str) -> Result<bool, Error> {
+    let node: serde_json::Value =
+        serde_json::from_str(json).map_err(|e| format!("Could not parse JSON: {}", e))?;
+    let ready = node["status"]["conditions"]
+        .as_array()
+        .ok_or("API server did not return a node")?
+        .iter()
+        .find(|condition| condition["type"] == "Ready")
+        .ok_or("Node has no Ready condition")?;
+
+    Ok(ready["status"] == "True")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::tests::read_request;
+
+    use std::io::Write;
+    use std::net::TcpListener;
+
+    const PODS: &str = r#"{
+        "kind": "PodList",
+        "items": [
+            {
+                "metadata": {"name": "oled-x7k2p"},
+                "spec": {"nodeName": "nas-1"},
+                "status": {"containerStatuses": [{"state": {"running": {}}}]}
+            },
+            {
+                "metadata": {"name": "backup-5d9f"},
+                "spec": {"nodeName": "nas-1"},
+                "status": {"containerStatuses": [
+                    {"state": {"running": {}}},
+                    {"state": {"waiting": {"reason": "CrashLoopBackOff"}}}
+                ]}
+            },
+            {
+                "metadata": {"name": "migrate-2x"},
+                "spec": {"nodeName": "nas-1"},
+                "status": {"initContainerStatuses": [
+                    {"state": {"waiting": {"reason": "CrashLoopBackOff"}}}
+                ]}
+            },
+            {
+                "metadata": {"name": "pulling-9q"},
+                "spec": {"nodeName": "nas-1"},
+                "status": {"containerStatuses": [
+                    {"state": {"waiting": {"reason": "ContainerCreating"}}}
+                ]}
+            }
+        ]
+    }"#;
+
+    const NOT_READY: &str = r#"{
+        "kind": "Node",
+        "metadata": {"name": "nas-1"},
+        "status": {"conditions": [
+            {"type": "MemoryPressure", "status": "False"},
+            {"type": "Ready", "status": "False", "reason": "KubeletNotReady"}
+        ]}
+    }"#;
+
+    /// Answers requests like a kubelet and an API server proxy would, until
+    /// `requests` were served
+    fn kubelet(pods: &'static str, node: &'static str, requests: usize) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming().take(requests) {
+                let mut stream = stream.unwrap();
+                let request = read_request(&mut stream);
+                let (status, body) = match request.split(' ').nth(1) {
+                    Some("/pods") => ("200 OK", pods),
+                    Some("/api/v1/nodes/nas-1") => ("200 OK", node),
+                    _ => ("404 Not Found", ""),
+                };
+                let response = format!("HTTP/1.0 {}\r\n\r\n{}", status, body);
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        url
+    }
+
+    #[test]
+    fn finds_crash_looping_pods() {
+        let pods = parse_pods(PODS).unwrap();
+
+        assert_eq!(pods.count, 4);
+        assert_eq!(pods.crashing, vec!["backup-5d9f", "migrate-2x"]);
+        assert_eq!(pods.node.as_deref(), Some("nas-1"));
+        assert!(parse_pods(r#"{"kind": "Status"}"#).is_err());
+    }
+
+    #[test]
+    fn reads_ready_condition() {
+        assert!(!parse_node_ready(NOT_READY).unwrap());
+        assert!(parse_node_ready(
+            r#"{"status": {"conditions": [{"type": "Ready", "status": "True"}]}}"#
+        )
+        .unwrap());
+        assert!(parse_node_ready(r#"{"status": {"conditions": []}}"#).is_err());
+        assert!(parse_node_ready(r#"{"kind": "Status", "code": 403}"#).is_err());
+    }
+
+    #[test]
+    fn reads_status_of_node_named_in_pods() {
+        let url = kubelet(PODS, NOT_READY, 2);
+        let mut kubernetes =
+            Kubernetes::new(&format!("{}/", url), &url, None, Duration::from_secs(5));
+
+        kubernetes.update().unwrap();
+        assert_eq!(kubernetes.ready, Some(false));
+        assert_eq!(kubernetes.pods.as_ref().map(|pods| pods.count), Some(4));
+        assert_eq!(kubernetes.height(10), 40);
+        assert!(kubernetes.alerting());
+    }
+
+    #[test]
+    fn unknown_node_has_no_ready_state() {
+        let url = kubelet(r#"{"items": []}"#, NOT_READY, 2);
+        let mut kubernetes = Kubernetes::new(
+            &url,
+            &url,
+            Some("nas-2".to_string()),
+            Duration::from_secs(5),
+        );
+
+        assert!(kubernetes.update().is_err());
+        assert_eq!(kubernetes.ready, None);
+        assert_eq!(kubernetes.pods.as_ref().map(|pods| pods.count), Some(0));
+        assert!(!kubernetes.alerting());
+    }
+
+    #[test]
+    fn sums_up_crashing_pods_that_do_not_fit() {
+        let mut kubernetes = Kubernetes::new("", "", None, Duration::from_secs(5));
+        kubernetes.pods = Some(Pods {
+            count: 5,
+            crashing: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            node: None,
+        });
+
+        assert_eq!(kubernetes.crashing_rows(), vec!["a", "+2 more"]);
+        assert_eq!(kubernetes.height(10), 40);
+    }
+}
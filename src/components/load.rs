@@ -1,18 +1,20 @@
-use std::collections::VecDeque;
+use super::graph::{draw_graph, GraphMode, Scale};
+use super::{Component, Drawer, Error, Snapshot};
+use crate::History;
 
-use super::{Component, Drawer, Error};
+use std::time::SystemTime;
 
 use systemstat::{
     data::{CPULoad, DelayedMeasurement},
     Platform, System,
 };
 
-use embedded_graphics::{prelude::*, primitives::Line};
+use embedded_graphics::prelude::*;
 
 pub struct Load {
     sys: System,
     measurement: Option<DelayedMeasurement<CPULoad>>,
-    graph_values: VecDeque<f32>,
+    history: History,
 }
 
 impl Load {
@@ -20,7 +22,7 @@ impl Load {
         Ok(Self {
             sys: System::new(),
             measurement: None,
-            graph_values: VecDeque::with_capacity(Drawer::WIDTH.into()), // FIFO, newest in front
+            history: History::new(Drawer::WIDTH.into()),
         })
     }
 
@@ -39,12 +41,7 @@ impl Load {
                 let result = measurement.done()?;
                 self.measurement = None;
 
-                if self.graph_values.capacity() == self.graph_values.len() {
-                    // at capacity, remove oldest data point
-                    self.graph_values.pop_back();
-                }
-
-                self.graph_values.push_front(result.user);
+                self.history.push(SystemTime::now(), result.user);
 
                 Ok(())
             }
@@ -64,14 +61,27 @@ impl std::fmt::Debug for Load {
     }
 }
 
+impl Snapshot for Load {
+    fn snapshot(&self) -> Box<dyn Component> {
+        // a measurement in progress can't be copied and isn't drawn anyway
+        Box::new(Self {
+            sys: System::new(),
+            measurement: None,
+            history: self.history.clone(),
+        })
+    }
+}
+
 impl Component for Load {
-    fn should_update(&self, last_update: std::time::Duration) -> bool {
-        last_update
-            > match self.measurement {
-                // when a measurement is in progress, update after 1 second to collect values
-                Some(_) => std::time::Duration::from_secs(1),
-                None => std::time::Duration::from_secs(60),
-            }
+    fn update_interval(
+        &self,
+        configured: Option<std::time::Duration>,
+    ) -> Option<std::time::Duration> {
+        Some(match self.measurement {
+            // when a measurement is in progress, update after 1 second to collect values
+            Some(_) => std::time::Duration::from_secs(1),
+            None => configured.unwrap_or(std::time::Duration::from_secs(60)),
+        })
     }
 
     fn update(&mut self) -> Result<(), Error> {
@@ -81,46 +91,23 @@ impl Component for Load {
         self.collect_measurement()
     }
 
-    fn draw(&self, drawable: &mut Drawer, offset: Point, _tick: u64) -> Result<(), Error> {
-        let graph_height = Drawer::LINE_HEIGHT - 1;
-
-        for (i, datum) in self.graph_values.iter().enumerate() {
-            Line::new(
-                Point::new(
-                    Drawer::WIDTH as i32 - i as i32,
-                    graph_height as i32 - (graph_height as f32 * datum) as i32,
-                ) + offset,
-                Point::new(
-                    Drawer::WIDTH as i32 - i as i32,
-                    graph_height as i32 - (graph_height as f32 * datum) as i32,
-                ) + offset,
-            )
-            .into_styled(drawable.base_primitive_style)
-            .draw(&mut drawable.display)?;
-        }
-
-        for i in 0..Drawer::WIDTH {
-            if i % 10 == 0 {
-                Line::new(
-                    Point::new(i.into(), graph_height.into()) + offset,
-                    Point::new(i.into(), graph_height.into()) + offset,
-                )
-                .into_styled(drawable.base_primitive_style)
-                .draw(&mut drawable.display)?;
-            }
-        }
+    fn history(&self) -> Option<&History> {
+        Some(&self.history)
+    }
 
-        Line::new(Point::new(0, 0) + offset, Point::new(0, 0) + offset)
-            .into_styled(drawable.base_primitive_style)
-            .draw(&mut drawable.display)?;
+    fn history_mut(&mut self) -> Option<&mut History> {
+        Some(&mut self.history)
+    }
 
-        Line::new(
-            Point::new(Drawer::WIDTH.into(), 0) + offset,
-            Point::new(Drawer::WIDTH.into(), 0) + offset,
+    fn draw(&self, drawable: &mut Drawer, offset: Point, _tick: u64) -> Result<(), Error> {
+        let size = Size::new(Drawer::WIDTH as u32 + 1, drawable.line_height());
+        draw_graph(
+            drawable,
+            offset,
+            size,
+            &self.history,
+            GraphMode::Line,
+            Scale::Fixed(1.0),
         )
-        .into_styled(drawable.base_primitive_style)
-        .draw(&mut drawable.display)?;
-
-        Ok(())
     }
 }
@@ -1,93 +1,389 @@
-use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
+use super::bar::draw_bar;
+use super::graph::{draw_graph, GraphMode, Scale};
+use super::text::draw_text;
 use super::{Component, Drawer, Error};
+use crate::{Base, GlancableSizesWithOrdersOfMagnitude, History};
 
-use systemstat::{Platform, System};
+use embedded_graphics::{
+    prelude::*,
+    text::{Alignment, Baseline, Text},
+};
 
-use embedded_graphics::{prelude::*, primitives::Line};
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+struct MemInfo {
+    total: u64,
+    available: u64,
+    swap_total: u64,
+    swap_free: u64,
+}
+
+impl MemInfo {
+    fn used(&self) -> u64 {
+        self.total.saturating_sub(self.available)
+    }
+
+    fn usage(&self) -> f32 {
+        fraction(self.used(), self.total)
+    }
+
+    fn swap_usage(&self) -> f32 {
+        fraction(
+            self.swap_total.saturating_sub(self.swap_free),
+            self.swap_total,
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+struct ZramStats {
+    disk_size: u64,
+    original_data_size: u64,
+}
 
+impl ZramStats {
+    fn usage(&self) -> f32 {
+        fraction(self.original_data_size, self.disk_size)
+    }
+}
+
+#[derive(Clone)]
 pub struct Memory {
-    sys: System,
-    graph_values: VecDeque<f32>,
+    meminfo_path: PathBuf,
+    block_sysfs: PathBuf,
+    show_swap: bool,
+    info: Option<MemInfo>,
+    zram: Option<ZramStats>,
+    history: History,
 }
 
 impl Memory {
-    pub fn new() -> Result<Self, Error> {
+    pub fn new(procfs: &Path, block_sysfs: &Path, show_swap: bool) -> Result<Self, Error> {
+        let meminfo_path = procfs.join("meminfo");
+        if !meminfo_path.is_file() {
+            return Err(format!("Could not find {}", meminfo_path.display()).into());
+        }
+
         Ok(Self {
-            sys: System::new(),
-            graph_values: VecDeque::with_capacity(Drawer::WIDTH.into()), // FIFO, newest in front
+            meminfo_path,
+            block_sysfs: block_sysfs.to_path_buf(),
+            show_swap,
+            info: None,
+            zram: None,
+            history: History::new(Drawer::WIDTH.into()),
         })
     }
+
+    fn draw_totals(&self, drawable: &mut Drawer, offset: Point, tick: u64) -> Result<(), Error> {
+        let text = match self.info {
+            Some(info) => format!(
+                "{}/{}",
+                GlancableSizesWithOrdersOfMagnitude::new(info.used(), Base::Two),
+                GlancableSizesWithOrdersOfMagnitude::new(info.total, Base::Two),
+            ),
+            None => "-/-".to_string(),
+        };
+        draw_text(
+            drawable,
+            &text,
+            offset,
+            Drawer::WIDTH as u32 + 1,
+            Alignment::Center,
+            tick,
+        )
+    }
+
+    fn draw_graph(&self, drawable: &mut Drawer, offset: Point) -> Result<(), Error> {
+        let size = Size::new(Drawer::WIDTH as u32 + 1, drawable.line_height());
+        draw_graph(
+            drawable,
+            offset,
+            size,
+            &self.history,
+            GraphMode::Line,
+            Scale::Fixed(1.0),
+        )
+    }
+
+    fn draw_swap(&self, drawable: &mut Drawer, offset: Point) -> Result<(), Error> {
+        let Some(info) = self.info else {
+            return Ok(());
+        };
+
+        match self.zram {
+            Some(zram) => {
+                let half_width = (Drawer::WIDTH as i32 + 1) / 2;
+                draw_labeled_bar(drawable, offset, half_width - 1, "S", info.swap_usage())?;
+                draw_labeled_bar(
+                    drawable,
+                    offset + Point::new(half_width, 0),
+                    Drawer::WIDTH as i32 - half_width,
+                    "Z",
+                    zram.usage(),
+                )
+            }
+            None => draw_labeled_bar(
+                drawable,
+                offset,
+                Drawer::WIDTH.into(),
+                "S",
+                info.swap_usage(),
+            ),
+        }
+    }
 }
 
 impl std::fmt::Display for Memory {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Load")
+        write!(f, "Memory")
     }
 }
 
 impl std::fmt::Debug for Memory {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Load")
+        write!(f, "Memory")
     }
 }
 
 impl Component for Memory {
-    fn should_update(&self, last_update: std::time::Duration) -> bool {
-        last_update > std::time::Duration::from_secs(60)
+    fn update_interval(
+        &self,
+        configured: Option<std::time::Duration>,
+    ) -> Option<std::time::Duration> {
+        Some(configured.unwrap_or(std::time::Duration::from_secs(60)))
     }
 
     fn update(&mut self) -> Result<(), Error> {
-        if self.graph_values.capacity() == self.graph_values.len() {
-            // at capacity, remove oldest data point
-            self.graph_values.pop_back();
+        let info = parse_meminfo(&fs::read_to_string(&self.meminfo_path)?)?;
+
+        self.history.push(SystemTime::now(), info.usage());
+        self.info = Some(info);
+
+        if self.show_swap {
+            self.zram = read_zram_stats(&self.block_sysfs)?;
         }
-        let usage = self.sys.memory()?;
-        self.graph_values
-            .push_front(1.0 - (usage.free.as_u64() as f32 / usage.total.as_u64() as f32));
+
         Ok(())
     }
 
-    fn draw(&self, drawable: &mut Drawer, offset: Point, _tick: u64) -> Result<(), Error> {
-        let graph_height = Drawer::LINE_HEIGHT - 1;
-
-        for (i, datum) in self.graph_values.iter().enumerate() {
-            Line::new(
-                Point::new(
-                    Drawer::WIDTH as i32 - i as i32,
-                    graph_height as i32 - (graph_height as f32 * datum) as i32,
-                ) + offset,
-                Point::new(
-                    Drawer::WIDTH as i32 - i as i32,
-                    graph_height as i32 - (graph_height as f32 * datum) as i32,
-                ) + offset,
-            )
-            .into_styled(drawable.base_primitive_style)
-            .draw(&mut drawable.display)?;
+    fn height(&self, line_height: u32) -> u32 {
+        match self.show_swap {
+            true => 3 * line_height,
+            false => 2 * line_height,
         }
+    }
 
-        for i in 0..Drawer::WIDTH {
-            if i % 10 == 0 {
-                Line::new(
-                    Point::new(i.into(), graph_height.into()) + offset,
-                    Point::new(i.into(), graph_height.into()) + offset,
-                )
-                .into_styled(drawable.base_primitive_style)
-                .draw(&mut drawable.display)?;
-            }
-        }
+    fn history(&self) -> Option<&History> {
+        Some(&self.history)
+    }
 
-        Line::new(Point::new(0, 0) + offset, Point::new(0, 0) + offset)
-            .into_styled(drawable.base_primitive_style)
-            .draw(&mut drawable.display)?;
+    fn history_mut(&mut self) -> Option<&mut History> {
+        Some(&mut self.history)
+    }
 
-        Line::new(
-            Point::new(Drawer::WIDTH.into(), 0) + offset,
-            Point::new(Drawer::WIDTH.into(), 0) + offset,
-        )
-        .into_styled(drawable.base_primitive_style)
-        .draw(&mut drawable.display)?;
+    fn draw(&self, drawable: &mut Drawer, offset: Point, tick: u64) -> Result<(), Error> {
+        self.draw_totals(drawable, offset, tick)?;
+        let line_height = drawable.line_height() as i32;
+        self.draw_graph(drawable, offset + Point::new(0, line_height))?;
+
+        if self.show_swap {
+            self.draw_swap(drawable, offset + Point::new(0, 2 * line_height))?;
+        }
 
         Ok(())
     }
 }
+
+fn fraction(part: u64, total: u64) -> f32 {
+    match total {
+        0 => 0.0,
+        total => (part as f64 / total as f64).clamp(0.0, 1.0) as f32,
+    }
+}
+
+fn draw_labeled_bar(
+    drawable: &mut Drawer,
+    offset: Point,
+    width: i32,
+    label: &str,
+    usage: f32,
+) -> Result<(), Error> {
+    let label = Text::with_baseline(label, offset, drawable.base_text_style, Baseline::Top);
+    let bar_start = label.bounding_box().size.width as i32 + 1;
+    label.draw(&mut drawable.display)?;
+
+    draw_bar(
+        drawable,
+        offset + Point::new(bar_start, 0),
+        width - bar_start,
+        usage,
+    )
+}
+
+fn parse_meminfo(meminfo: &str) -> Result<MemInfo, Error> {
+    let mut total = None;
+    let mut available = None;
+    let mut free = 0;
+    let mut buffers = 0;
+    let mut cached = 0;
+    let mut swap_total = 0;
+    let mut swap_free = 0;
+
+    for line in meminfo.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim().trim_end_matches("kB").trim();
+        let value: u64 = value
+            .parse::<u64>()
+            .map_err(|_| format!("Could not parse meminfo value for {}", key))?
+            * 1024;
+
+        match key {
+            "MemTotal" => total = Some(value),
+            "MemAvailable" => available = Some(value),
+            "MemFree" => free = value,
+            "Buffers" => buffers = value,
+            "Cached" => cached = value,
+            "SwapTotal" => swap_total = value,
+            "SwapFree" => swap_free = value,
+            _ => (),
+        }
+    }
+
+    Ok(MemInfo {
+        total: total.ok_or("meminfo is missing MemTotal")?,
+        // kernels before 3.14 don't report MemAvailable, so approximate it
+        available: available.unwrap_or(free + buffers + cached),
+        swap_total,
+        swap_free,
+    })
+}
+
+fn read_zram_stats(block_sysfs: &Path) -> Result<Option<ZramStats>, Error> {
+    let mut stats: Option<ZramStats> = None;
+
+    for device in block_sysfs.read_dir()? {
+        let device = device?.path();
+        let is_zram = device
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with("zram"));
+        if !is_zram {
+            continue;
+        }
+
+        let disk_size: u64 = fs::read_to_string(device.join("disksize"))?
+            .trim()
+            .parse()
+            .map_err(|_| format!("Could not parse zram size of {}", device.display()))?;
+        if disk_size == 0 {
+            // unconfigured zram device
+            continue;
+        }
+
+        let mm_stat = fs::read_to_string(device.join("mm_stat"))?;
+        let original_data_size = parse_mm_stat(&mm_stat)
+            .ok_or_else(|| format!("Could not parse zram stats of {}", device.display()))?;
+
+        let total = stats.get_or_insert_with(ZramStats::default);
+        total.disk_size += disk_size;
+        total.original_data_size += original_data_size;
+    }
+
+    Ok(stats)
+}
+
+fn parse_mm_stat(mm_stat: &str) -> Option<u64> {
+    // the first column is the uncompressed size of the stored data
+    mm_stat.split_whitespace().next()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MEMINFO: &str = "MemTotal:        3867020 kB
+MemFree:          149876 kB
+MemAvailable:    2917764 kB
+Buffers:           97916 kB
+Cached:          2580664 kB
+SwapCached:            0 kB
+SwapTotal:       1933508 kB
+SwapFree:        1450131 kB
+HugePages_Total:       0
+";
+
+    #[test]
+    fn meminfo_uses_available_memory() {
+        let info = parse_meminfo(MEMINFO).unwrap();
+
+        assert_eq!(info.total, 3867020 * 1024);
+        assert_eq!(info.available, 2917764 * 1024);
+        assert_eq!(info.used(), (3867020 - 2917764) * 1024);
+        assert!((info.usage() - 0.2455).abs() < 0.001);
+    }
+
+    #[test]
+    fn meminfo_reads_swap() {
+        let info = parse_meminfo(MEMINFO).unwrap();
+
+        assert_eq!(info.swap_total, 1933508 * 1024);
+        assert_eq!(info.swap_free, 1450131 * 1024);
+        assert!((info.swap_usage() - 0.25).abs() < 0.001);
+    }
+
+    #[test]
+    fn meminfo_approximates_missing_available_memory() {
+        let info =
+            parse_meminfo("MemTotal: 1000 kB\nMemFree: 100 kB\nBuffers: 50 kB\nCached: 250 kB\n")
+                .unwrap();
+
+        assert_eq!(info.available, 400 * 1024);
+        assert_eq!(info.swap_usage(), 0.0);
+    }
+
+    #[test]
+    fn meminfo_requires_total() {
+        assert!(parse_meminfo("MemFree: 100 kB\n").is_err());
+    }
+
+    #[test]
+    fn zram_stats_are_summed_over_configured_devices() {
+        let root =
+            std::env::temp_dir().join(format!("oled-memory-test-zram-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for (name, size, mm_stat) in [
+            ("zram0", "1000\n", "250 100 120 0 120 0 0 0 0\n"),
+            ("zram1", "0\n", "0 0 0 0 0 0 0 0 0\n"),
+            ("zram2", "1000\n", "750 300 320 0 320 0 0 0 0\n"),
+        ] {
+            fs::create_dir_all(root.join(name)).unwrap();
+            fs::write(root.join(name).join("disksize"), size).unwrap();
+            fs::write(root.join(name).join("mm_stat"), mm_stat).unwrap();
+        }
+        fs::create_dir_all(root.join("sda")).unwrap();
+
+        let stats = read_zram_stats(&root).unwrap().unwrap();
+        assert_eq!(
+            stats,
+            ZramStats {
+                disk_size: 2000,
+                original_data_size: 1000,
+            }
+        );
+        assert_eq!(stats.usage(), 0.5);
+    }
+
+    #[test]
+    fn zram_stats_are_none_without_devices() {
+        let root =
+            std::env::temp_dir().join(format!("oled-memory-test-nozram-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("sda")).unwrap();
+
+        assert_eq!(read_zram_stats(&root).unwrap(), None);
+    }
+}
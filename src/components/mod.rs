@@ -1,15 +1,53 @@
-pub use crate::{Drawer, Error};
+pub use crate::{Drawer, Error, History};
+
+use std::time::Duration;
 
 // pub trait Component<D>: std::fmt::Debug
 // where
 //     D: embedded_graphics::prelude::DrawTarget<Color = embedded_graphics::pixelcolor::BinaryColor, Error = display_interface::DisplayError>,
 // {
 
-pub trait Component: std::fmt::Debug + std::fmt::Display {
-    fn should_update(&self, last_update: std::time::Duration) -> bool;
+/// A copy of a component that can be drawn while the component itself is
+/// being updated on another thread
+pub trait Snapshot {
+    fn snapshot(&self) -> Box<dyn Component>;
+}
+
+impl<T: Component + Clone + 'static> Snapshot for T {
+    fn snapshot(&self) -> Box<dyn Component> {
+        Box::new(self.clone())
+    }
+}
+
+pub trait Component: Snapshot + Send + std::fmt::Debug + std::fmt::Display {
+    /// How long after an update the component wants to be updated again,
+    /// `None` if it never does. `configured` is the interval the user set for
+    /// this component, if any, and replaces its regular interval.
+    fn update_interval(&self, configured: Option<Duration>) -> Option<Duration>;
 
     fn update(&mut self) -> Result<(), Error>;
 
+    /// How many pixels high the component is, given the height of a line in
+    /// its font.
+    fn height(&self, line_height: u32) -> u32 {
+        line_height
+    }
+
+    /// Whether the component wants to draw attention to itself. The drawer
+    /// highlights alerting components.
+    fn alerting(&self) -> bool {
+        false
+    }
+
+    /// History worth keeping across restarts, if the component has any
+    fn history(&self) -> Option<&History> {
+        None
+    }
+
+    fn history_mut(&mut self) -> Option<&mut History> {
+        None
+    }
+
     fn draw(
         &self,
         drawable: &mut Drawer,
@@ -18,18 +56,52 @@ pub trait Component: std::fmt::Debug + std::fmt::Display {
     ) -> Result<(), Error>;
 }
 
+mod address;
+mod bar;
+mod clock;
+mod command;
+mod containers;
 mod disk;
+mod graph;
 mod hostname;
+#[cfg(feature = "kubernetes")]
+mod kubernetes;
 mod load;
 mod memory;
 mod network;
+mod pressure;
+mod probe;
+mod qr_code;
+mod status_file;
+mod systemd;
+mod text;
 mod update_indicator;
+mod updates;
+mod ups;
 mod uptime;
+mod wireguard;
 
+pub use self::address::Address;
+pub use self::clock::Clock;
+pub use self::command::{Command, CommandSpec};
+pub use self::containers::Containers;
 pub use self::disk::Disk;
+pub use self::graph::GraphMode;
 pub use self::hostname::Hostname;
+#[cfg(feature = "kubernetes")]
+pub use self::kubernetes::Kubernetes;
 pub use self::load::Load;
 pub use self::memory::Memory;
-pub use self::network::NetworkThroughput;
+pub use self::network::{
+    NetworkGraph, NetworkOptions, NetworkSource, NetworkThroughput, PeakWindow,
+};
+pub use self::pressure::Pressure;
+pub use self::probe::{Probe, ProbeTarget};
+pub use self::qr_code::QrCode;
+pub use self::status_file::{StatusFile, StatusFileSpec};
+pub use self::systemd::Systemd;
 pub use self::update_indicator::UpdateIndicator;
+pub use self::updates::Updates;
+pub use self::ups::{Ups, UpsSpec};
 pub use self::uptime::Uptime;
+pub use self::wireguard::{WireGuard, WireGuardSpec};
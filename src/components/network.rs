@@ -1,34 +1,117 @@
-use super::{Component, Drawer, Error};
+use super::graph::{draw_mirrored_graph, GraphMode, Scale};
+use super::text::draw_text;
+use super::{Component, Drawer, Error, History};
 
 use embedded_graphics::{
     prelude::*,
     primitives::Line,
-    text::{Baseline, Text},
+    text::{Alignment, Baseline, Text},
 };
 
 use std::{
+    collections::VecDeque,
     fs,
     path::{Path, PathBuf},
-    time::{Duration, Instant},
+    time::{Duration, Instant, SystemTime},
 };
 
 const ARROW_WIDTH: i32 = 3;
+const ARROW_HEIGHT: i32 = 5;
 const ELEMENT_PADDING: i32 = 1;
-const FONT_WIDTH: i32 = 6;
-const RATE_WIDTH: i32 = 4 * FONT_WIDTH;
-const GROUP_WIDTH: i32 = ARROW_WIDTH + ELEMENT_PADDING + RATE_WIDTH;
-const GROUP_GAP: i32 = Drawer::WIDTH as i32 - (2 * GROUP_WIDTH);
+// characters reserved for a formatted rate, in fonts narrow enough
+const RATE_CHARACTERS: i32 = 4;
+// room for the fault mark between the directions
+const MARK_GAP: i32 = 3;
+const HOUR: Duration = Duration::from_secs(60 * 60);
+// ticks each adapter is shown for when several take turns
+const ROTATION_TICKS: u64 = 5;
+// pattern for the sum of all physical adapters
+const PHYSICAL: &str = "physical";
+const BYTE_UNITS: [&str; 7] = ["B", "K", "M", "G", "T", "P", "E"];
+// plain metric prefixes, the label line says that these are packets
+const PACKET_UNITS: [&str; 7] = ["", "K", "M", "G", "T", "P", "E"];
+const PACKET_LABEL: &str = "pps";
+
+/// The scale of the throughput history graph
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum NetworkGraph {
+    Auto,
+    Log,
+}
+
+impl std::str::FromStr for NetworkGraph {
+    type Err = Error;
+
+    fn from_str(scale: &str) -> Result<Self, Self::Err> {
+        match scale {
+            "auto" => Ok(Self::Auto),
+            "log" => Ok(Self::Log),
+            unknown => {
+                Err(format!("{} is not a known graph scale, use auto or log", unknown).into())
+            }
+        }
+    }
+}
 
+/// Over which time the peak rates are shown
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PeakWindow {
+    /// Since the service started
+    Start,
+    /// Within the last hour
+    Hour,
+}
+
+impl std::str::FromStr for PeakWindow {
+    type Err = Error;
+
+    fn from_str(window: &str) -> Result<Self, Self::Err> {
+        match window {
+            "start" => Ok(Self::Start),
+            "hour" => Ok(Self::Hour),
+            unknown => {
+                Err(format!("{} is not a known peak window, use start or hour", unknown).into())
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
 struct NetworkCounters {
     rx_bytes: u64,
     tx_bytes: u64,
+    rx_packets: u64,
+    tx_packets: u64,
+    // errors, drops and carrier changes, which all hint at a bad link
+    faults: u64,
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
 struct NetworkRates {
     rx_bytes_per_second: u64,
     tx_bytes_per_second: u64,
+    rx_packets_per_second: u64,
+    tx_packets_per_second: u64,
+}
+
+impl NetworkRates {
+    /// The higher of both rates in each direction
+    fn max(self, other: Self) -> Self {
+        Self {
+            rx_bytes_per_second: self.rx_bytes_per_second.max(other.rx_bytes_per_second),
+            tx_bytes_per_second: self.tx_bytes_per_second.max(other.tx_bytes_per_second),
+            rx_packets_per_second: self.rx_packets_per_second.max(other.rx_packets_per_second),
+            tx_packets_per_second: self.tx_packets_per_second.max(other.tx_packets_per_second),
+        }
+    }
+
+    /// The received and sent rate, in packets or bytes per second
+    fn rx_tx(&self, packets: bool) -> (u64, u64) {
+        match packets {
+            true => (self.rx_packets_per_second, self.tx_packets_per_second),
+            false => (self.rx_bytes_per_second, self.tx_bytes_per_second),
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -37,91 +120,391 @@ struct NetworkSample {
     measured_at: Instant,
 }
 
-#[derive(Debug)]
-pub struct NetworkThroughput {
-    name: String,
-    interface_path: PathBuf,
-    previous_sample: Option<NetworkSample>,
-    rates: Option<NetworkRates>,
+/// The highest rates seen within a window
+#[derive(Clone, Debug)]
+struct PeakRates {
+    window: PeakWindow,
+    // for the last hour every rate within it, oldest first, since start only
+    // the peak itself
+    rates: VecDeque<(Instant, NetworkRates)>,
 }
 
-impl NetworkThroughput {
-    pub fn new(name: String, sysfs_root: &Path) -> Result<Self, Error> {
-        let interface_path = find_interface_path(sysfs_root, &name)?;
+impl PeakRates {
+    fn new(window: PeakWindow) -> Self {
+        Self {
+            window,
+            rates: VecDeque::new(),
+        }
+    }
 
-        Ok(Self {
-            name,
-            interface_path,
-            previous_sample: None,
-            rates: None,
-        })
+    fn observe(&mut self, measured_at: Instant, rates: NetworkRates) {
+        match self.window {
+            PeakWindow::Start => {
+                let peak = self.peak().map_or(rates, |peak| peak.max(rates));
+                self.rates = VecDeque::from([(measured_at, peak)]);
+            }
+            PeakWindow::Hour => {
+                self.rates.push_back((measured_at, rates));
+                while let Some((oldest, _)) = self.rates.front() {
+                    if measured_at.duration_since(*oldest) <= HOUR {
+                        break;
+                    }
+                    self.rates.pop_front();
+                }
+            }
+        }
     }
 
-    fn read_counters(&self) -> Result<NetworkCounters, Error> {
-        read_counters(&self.interface_path)
+    fn peak(&self) -> Option<NetworkRates> {
+        self.rates
+            .iter()
+            .map(|(_, rates)| *rates)
+            .reduce(NetworkRates::max)
     }
 }
 
-impl std::fmt::Display for NetworkThroughput {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Network {}", self.name)
-    }
+/// One or more network adapters whose traffic is shown under one label
+#[derive(Clone, Debug, PartialEq)]
+pub struct NetworkSource {
+    label: String,
+    interface_paths: Vec<PathBuf>,
 }
 
-impl Component for NetworkThroughput {
-    fn should_update(&self, last_update: std::time::Duration) -> bool {
-        last_update > std::time::Duration::from_secs(5)
+impl NetworkSource {
+    /// The adapters given by `pattern` in `sysfs_root`: a name, a glob like
+    /// `eth*` matching one source per adapter, or `physical` for the sum of
+    /// all adapters backed by a device.
+    pub fn resolve(pattern: &str, sysfs_root: &Path) -> Result<Vec<Self>, Error> {
+        if pattern == PHYSICAL {
+            let interface_paths: Vec<PathBuf> = interface_names(sysfs_root)?
+                .into_iter()
+                .map(|name| sysfs_root.join(name))
+                // virtual adapters like bridges, bonds or tunnels have no device
+                .filter(|path| path.join("device").exists())
+                .collect();
+            if interface_paths.is_empty() {
+                return Err(format!(
+                    "Could not find physical network adapters in {}",
+                    sysfs_root.display()
+                )
+                .into());
+            }
+
+            return Ok(vec![Self {
+                label: PHYSICAL.to_string(),
+                interface_paths,
+            }]);
+        }
+
+        if pattern.contains(['*', '?']) {
+            let sources: Vec<Self> = interface_names(sysfs_root)?
+                .into_iter()
+                .filter(|name| glob_matches(pattern, name))
+                .map(|name| Self {
+                    interface_paths: vec![sysfs_root.join(&name)],
+                    label: name,
+                })
+                .collect();
+            if sources.is_empty() {
+                return Err(format!(
+                    "No network adapter in {} matches '{}'",
+                    sysfs_root.display(),
+                    pattern
+                )
+                .into());
+            }
+
+            return Ok(sources);
+        }
+
+        Ok(vec![Self {
+            interface_paths: vec![find_interface_path(sysfs_root, pattern)?],
+            label: pattern.trim_end_matches(':').to_string(),
+        }])
     }
+}
 
-    fn update(&mut self) -> Result<(), Error> {
+/// How network throughput is shown
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NetworkOptions {
+    /// Graph the history of the rates below them
+    pub graph: Option<NetworkGraph>,
+    /// Show the peak rates below the graph
+    pub peak: Option<PeakWindow>,
+    /// Name the adapter above the rates
+    pub label: bool,
+    /// Show packets instead of bytes per second
+    pub packets: bool,
+}
+
+#[derive(Clone, Debug)]
+struct Interface {
+    source: NetworkSource,
+    previous_sample: Option<NetworkSample>,
+    rates: Option<NetworkRates>,
+    // whether faults increased since the previous sample
+    faulty: bool,
+    rx_history: History,
+    tx_history: History,
+    peak: Option<PeakRates>,
+}
+
+impl Interface {
+    fn update(&mut self, packets: bool) -> Result<(), Error> {
         let sample = NetworkSample {
-            counters: self.read_counters()?,
+            counters: read_counters(&self.source.interface_paths)?,
             measured_at: Instant::now(),
         };
 
         self.rates = self
             .previous_sample
             .and_then(|previous_sample| calculate_rates(previous_sample, sample));
+        self.faulty = self.previous_sample.is_some_and(|previous_sample| {
+            sample.counters.faults > previous_sample.counters.faults
+        });
         self.previous_sample = Some(sample);
 
+        if let Some(rates) = self.rates {
+            let now = SystemTime::now();
+            let (rx, tx) = rates.rx_tx(packets);
+            self.rx_history.push(now, rx as f32);
+            self.tx_history.push(now, tx as f32);
+
+            if let Some(peak) = &mut self.peak {
+                peak.observe(sample.measured_at, rates);
+            }
+        }
+
         Ok(())
     }
+}
+
+/// Network throughput of one source, or of several taking turns on the
+/// same rows
+#[derive(Clone, Debug)]
+pub struct NetworkThroughput {
+    interfaces: Vec<Interface>,
+    options: NetworkOptions,
+}
+
+impl NetworkThroughput {
+    pub fn new(sources: Vec<NetworkSource>, options: NetworkOptions) -> Result<Self, Error> {
+        if sources.is_empty() {
+            return Err("no network adapters to show".into());
+        }
+
+        Ok(Self {
+            interfaces: sources
+                .into_iter()
+                .map(|source| Interface {
+                    source,
+                    previous_sample: None,
+                    rates: None,
+                    faulty: false,
+                    rx_history: History::new(Drawer::WIDTH.into()),
+                    tx_history: History::new(Drawer::WIDTH.into()),
+                    peak: options.peak.map(PeakRates::new),
+                })
+                .collect(),
+            options,
+        })
+    }
+
+    /// Draws the rates with arrows next to them. Arrows of `peak` rates end
+    /// in a bar, `faulty` rates get a warning mark.
+    fn draw_rates(
+        &self,
+        drawable: &mut Drawer,
+        offset: Point,
+        rates: Option<NetworkRates>,
+        peak: bool,
+        faulty: bool,
+    ) -> Result<(), Error> {
+        let font = drawable.base_text_style.font;
+        let character_width = (font.character_size.width + font.character_spacing) as i32;
+        let characters = rate_characters(character_width);
+        let rate_width = characters * character_width;
+        let group_width = ARROW_WIDTH + ELEMENT_PADDING + rate_width;
+        let group_gap = Drawer::WIDTH as i32 - (2 * group_width);
+        // the bar above peak arrows needs a row of room
+        let arrow_top = ((drawable.line_height() as i32 - 1 - ARROW_HEIGHT) / 2).max(peak.into());
 
-    fn draw(&self, drawable: &mut Drawer, offset: Point, _tick: u64) -> Result<(), Error> {
         let rx_offset = offset;
-        let tx_offset = offset + Point::new(GROUP_WIDTH + GROUP_GAP, 0);
-        let rx_rate = format_rate(self.rates.map(|rates| rates.rx_bytes_per_second));
-        let tx_rate = format_rate(self.rates.map(|rates| rates.tx_bytes_per_second));
+        let tx_offset = offset + Point::new(group_width + group_gap, 0);
+        let units = match self.options.packets {
+            true => &PACKET_UNITS,
+            false => &BYTE_UNITS,
+        };
+        let rx_tx = rates.map(|rates| rates.rx_tx(self.options.packets));
+        let rx_rate = format_rate(rx_tx.map(|(rx, _)| rx), units, characters as usize);
+        let tx_rate = format_rate(rx_tx.map(|(_, tx)| tx), units, characters as usize);
 
         Text::with_baseline(
             &rx_rate,
-            rx_offset + Point::new(RATE_WIDTH - rx_rate.len() as i32 * FONT_WIDTH, 0),
+            rx_offset + Point::new(rate_width - rx_rate.len() as i32 * character_width, 0),
             drawable.base_text_style,
             Baseline::Top,
         )
         .draw(&mut drawable.display)?;
         draw_down_arrow(
             drawable,
-            rx_offset + Point::new(RATE_WIDTH + ELEMENT_PADDING, 2),
+            rx_offset + Point::new(rate_width + ELEMENT_PADDING, arrow_top),
+            peak,
         )?;
 
         Text::with_baseline(
             &tx_rate,
-            tx_offset + Point::new(RATE_WIDTH - tx_rate.len() as i32 * FONT_WIDTH, 0),
+            tx_offset + Point::new(rate_width - tx_rate.len() as i32 * character_width, 0),
             drawable.base_text_style,
             Baseline::Top,
         )
         .draw(&mut drawable.display)?;
         draw_up_arrow(
             drawable,
-            tx_offset + Point::new(RATE_WIDTH + ELEMENT_PADDING, 2),
+            tx_offset + Point::new(rate_width + ELEMENT_PADDING, arrow_top),
+            peak,
         )?;
 
+        if faulty {
+            // an exclamation mark between the directions
+            let mark = offset + Point::new(group_width + group_gap / 2, arrow_top);
+            Line::new(mark, mark + Point::new(0, ARROW_HEIGHT - 3))
+                .into_styled(drawable.base_primitive_style)
+                .draw(&mut drawable.display)?;
+            Line::new(
+                mark + Point::new(0, ARROW_HEIGHT - 1),
+                mark + Point::new(0, ARROW_HEIGHT - 1),
+            )
+            .into_styled(drawable.base_primitive_style)
+            .draw(&mut drawable.display)?;
+        }
+
         Ok(())
     }
 }
 
-fn find_interface_path(sysfs_root: &Path, name: &str) -> Result<PathBuf, Error> {
+impl std::fmt::Display for NetworkThroughput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.interfaces.as_slice() {
+            [interface] => write!(f, "Network {}", interface.source.label),
+            _ => write!(f, "Network"),
+        }
+    }
+}
+
+impl Component for NetworkThroughput {
+    fn update_interval(
+        &self,
+        configured: Option<std::time::Duration>,
+    ) -> Option<std::time::Duration> {
+        Some(configured.unwrap_or(std::time::Duration::from_secs(5)))
+    }
+
+    fn update(&mut self) -> Result<(), Error> {
+        for interface in &mut self.interfaces {
+            interface.update(self.options.packets)?;
+        }
+
+        Ok(())
+    }
+
+    fn height(&self, line_height: u32) -> u32 {
+        // packet rates have their unit on the label line
+        let label_lines = match self.options.label || self.options.packets {
+            true => 1,
+            false => 0,
+        };
+        let graph_lines = match self.options.graph {
+            Some(_) => 2,
+            None => 0,
+        };
+        let peak_lines = match self.options.peak {
+            Some(_) => 1,
+            None => 0,
+        };
+        line_height * (label_lines + 1 + graph_lines + peak_lines)
+    }
+
+    fn draw(&self, drawable: &mut Drawer, offset: Point, tick: u64) -> Result<(), Error> {
+        let line_height = drawable.line_height() as i32;
+        let interface = &self.interfaces[(tick / ROTATION_TICKS) as usize % self.interfaces.len()];
+        let mut y = 0;
+
+        if self.options.label || self.options.packets {
+            let width = Drawer::WIDTH as u32 + 1;
+            let mut label_width = width;
+            if self.options.packets {
+                let font = drawable.base_text_style.font;
+                let character_width = font.character_size.width + font.character_spacing;
+                label_width -= (PACKET_LABEL.len() as u32 + 1) * character_width;
+                draw_text(
+                    drawable,
+                    PACKET_LABEL,
+                    offset,
+                    width,
+                    Alignment::Right,
+                    tick,
+                )?;
+            }
+            if self.options.label {
+                draw_text(
+                    drawable,
+                    &interface.source.label,
+                    offset,
+                    label_width,
+                    Alignment::Left,
+                    tick,
+                )?;
+            }
+            y += line_height;
+        }
+
+        self.draw_rates(
+            drawable,
+            offset + Point::new(0, y),
+            interface.rates,
+            false,
+            interface.faulty,
+        )?;
+        y += line_height;
+
+        if let Some(graph) = self.options.graph {
+            let label: fn(f32) -> String = match self.options.packets {
+                true => {
+                    |max| format_rate(Some(max as u64), &PACKET_UNITS, RATE_CHARACTERS as usize)
+                }
+                false => |max| format_rate(Some(max as u64), &BYTE_UNITS, RATE_CHARACTERS as usize),
+            };
+            draw_mirrored_graph(
+                drawable,
+                offset + Point::new(0, y),
+                Size::new(Drawer::WIDTH as u32 + 1, 2 * line_height as u32 - 1),
+                &interface.rx_history,
+                &interface.tx_history,
+                GraphMode::Filled,
+                match graph {
+                    NetworkGraph::Auto => Scale::Auto(label),
+                    NetworkGraph::Log => Scale::Log(label),
+                },
+            )?;
+            y += 2 * line_height;
+        }
+
+        if let Some(peak) = &interface.peak {
+            self.draw_rates(
+                drawable,
+                offset + Point::new(0, y),
+                peak.peak(),
+                true,
+                false,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+pub(super) fn find_interface_path(sysfs_root: &Path, name: &str) -> Result<PathBuf, Error> {
     let name = name.trim_end_matches(':');
 
     if name.is_empty() {
@@ -158,12 +541,70 @@ fn candidate_interface_names(name: &str) -> Vec<&str> {
     vec![name]
 }
 
-fn read_counters(interface_path: &Path) -> Result<NetworkCounters, Error> {
-    let statistics_path = interface_path.join("statistics");
-    Ok(NetworkCounters {
-        rx_bytes: read_counter(&statistics_path.join("rx_bytes"))?,
-        tx_bytes: read_counter(&statistics_path.join("tx_bytes"))?,
-    })
+/// The counters of `interface_paths` added up
+fn read_counters(interface_paths: &[PathBuf]) -> Result<NetworkCounters, Error> {
+    let mut counters = NetworkCounters::default();
+
+    for interface_path in interface_paths {
+        let statistics_path = interface_path.join("statistics");
+        counters.rx_bytes += read_counter(&statistics_path.join("rx_bytes"))?;
+        counters.tx_bytes += read_counter(&statistics_path.join("tx_bytes"))?;
+        counters.rx_packets += read_optional_counter(&statistics_path.join("rx_packets"))?;
+        counters.tx_packets += read_optional_counter(&statistics_path.join("tx_packets"))?;
+        for fault in ["rx_errors", "rx_dropped", "tx_errors"] {
+            counters.faults += read_optional_counter(&statistics_path.join(fault))?;
+        }
+        counters.faults += read_optional_counter(&interface_path.join("carrier_changes"))?;
+    }
+
+    Ok(counters)
+}
+
+/// Names of all network adapters in `sysfs_root`, sorted
+fn interface_names(sysfs_root: &Path) -> Result<Vec<String>, Error> {
+    let mut names = Vec::new();
+    for entry in fs::read_dir(sysfs_root)
+        .map_err(|e| format!("Could not list {}: {}", sysfs_root.display(), e))?
+    {
+        names.push(entry?.file_name().to_string_lossy().to_string());
+    }
+    names.sort();
+    Ok(names)
+}
+
+/// Whether `name` matches `pattern`, where `*` matches any number of
+/// characters and `?` a single one
+fn glob_matches(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    // positions to retry from after a mismatch, for the most recent `*`
+    let (mut p, mut n) = (0, 0);
+    let mut retry: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                retry = Some((p, n));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match retry {
+                // let the `*` swallow one more character
+                Some((star, skipped)) => {
+                    p = star + 1;
+                    n = skipped + 1;
+                    retry = Some((star, skipped + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
 }
 
 fn read_counter(path: &Path) -> Result<u64, Error> {
@@ -173,6 +614,15 @@ fn read_counter(path: &Path) -> Result<u64, Error> {
         .map_err(|_| format!("Could not parse network counter {}", path.display()))?)
 }
 
+/// Like `read_counter`, but 0 for counters that not every driver or kernel
+/// provides
+fn read_optional_counter(path: &Path) -> Result<u64, Error> {
+    match path.exists() {
+        true => read_counter(path),
+        false => Ok(0),
+    }
+}
+
 fn calculate_rates(previous_sample: NetworkSample, sample: NetworkSample) -> Option<NetworkRates> {
     let elapsed = sample
         .measured_at
@@ -197,6 +647,20 @@ fn calculate_rates(previous_sample: NetworkSample, sample: NetworkSample) -> Opt
                 .saturating_sub(previous_sample.counters.tx_bytes),
             elapsed,
         ),
+        rx_packets_per_second: bytes_per_second(
+            sample
+                .counters
+                .rx_packets
+                .saturating_sub(previous_sample.counters.rx_packets),
+            elapsed,
+        ),
+        tx_packets_per_second: bytes_per_second(
+            sample
+                .counters
+                .tx_packets
+                .saturating_sub(previous_sample.counters.tx_packets),
+            elapsed,
+        ),
     })
 }
 
@@ -204,16 +668,27 @@ fn bytes_per_second(bytes: u64, elapsed: Duration) -> u64 {
     ((bytes as u128 * 1_000_000_000) / elapsed.as_nanos()) as u64
 }
 
-fn format_rate(bytes_per_second: Option<u64>) -> String {
-    let Some(bytes_per_second) = bytes_per_second else {
-        return "-".to_string();
+/// How many characters of a rate fit next to each other with their arrows
+/// in a font `character_width` pixels wide, at most `RATE_CHARACTERS`
+fn rate_characters(character_width: i32) -> i32 {
+    let group_width = (Drawer::WIDTH as i32 - MARK_GAP) / 2;
+    ((group_width - ARROW_WIDTH - ELEMENT_PADDING) / character_width).clamp(1, RATE_CHARACTERS)
+}
+
+/// `per_second` in at most `characters` characters. Rates that don't fit are
+/// rounded up to a larger unit, so they are never understated.
+fn format_rate(per_second: Option<u64>, units: &[&str; 7], characters: usize) -> String {
+    let Some(per_second) = per_second else {
+        return match characters {
+            c if c >= 3 => "-.-".to_string(),
+            _ => "-".to_string(),
+        };
     };
 
-    const UNITS: [&str; 7] = ["B", "K", "M", "G", "T", "P", "E"];
-    let mut value = bytes_per_second;
+    let mut value = per_second;
     let mut magnitude = 0usize;
 
-    while value >= 1000 && magnitude + 1 < UNITS.len() {
+    while value >= 1000 && magnitude + 1 < units.len() {
         let previous_value = value;
         value /= 1000;
         magnitude += 1;
@@ -223,10 +698,17 @@ fn format_rate(bytes_per_second: Option<u64>) -> String {
         }
     }
 
-    format!("{}{}", value, UNITS[magnitude])
+    while (value.to_string().len() + units[magnitude].len()) > characters
+        && magnitude + 1 < units.len()
+    {
+        value = value.div_ceil(1000);
+        magnitude += 1;
+    }
+
+    format!("{}{}", value, units[magnitude])
 }
 
-fn draw_down_arrow(drawable: &mut Drawer, offset: Point) -> Result<(), Error> {
+fn draw_down_arrow(drawable: &mut Drawer, offset: Point, bar: bool) -> Result<(), Error> {
     Line::new(offset + Point::new(1, 0), offset + Point::new(1, 4))
         .into_styled(drawable.base_primitive_style)
         .draw(&mut drawable.display)?;
@@ -236,11 +718,16 @@ fn draw_down_arrow(drawable: &mut Drawer, offset: Point) -> Result<(), Error> {
     Line::new(offset + Point::new(2, 3), offset + Point::new(1, 4))
         .into_styled(drawable.base_primitive_style)
         .draw(&mut drawable.display)?;
+    if bar {
+        Line::new(offset + Point::new(0, 5), offset + Point::new(2, 5))
+            .into_styled(drawable.base_primitive_style)
+            .draw(&mut drawable.display)?;
+    }
 
     Ok(())
 }
 
-fn draw_up_arrow(drawable: &mut Drawer, offset: Point) -> Result<(), Error> {
+fn draw_up_arrow(drawable: &mut Drawer, offset: Point, bar: bool) -> Result<(), Error> {
     Line::new(offset + Point::new(1, 0), offset + Point::new(1, 4))
         .into_styled(drawable.base_primitive_style)
         .draw(&mut drawable.display)?;
@@ -250,6 +737,11 @@ fn draw_up_arrow(drawable: &mut Drawer, offset: Point) -> Result<(), Error> {
     Line::new(offset + Point::new(2, 1), offset + Point::new(1, 0))
         .into_styled(drawable.base_primitive_style)
         .draw(&mut drawable.display)?;
+    if bar {
+        Line::new(offset + Point::new(0, -1), offset + Point::new(2, -1))
+            .into_styled(drawable.base_primitive_style)
+            .draw(&mut drawable.display)?;
+    }
 
     Ok(())
 }
@@ -258,6 +750,7 @@ fn draw_up_arrow(drawable: &mut Drawer, offset: Point) -> Result<(), Error> {
 mod tests {
     use super::*;
 
+    use embedded_graphics::mono_font::ascii::FONT_10X20;
     use std::io::Write;
 
     #[test]
@@ -286,6 +779,7 @@ mod tests {
             counters: NetworkCounters {
                 rx_bytes: 100,
                 tx_bytes: 200,
+                ..Default::default()
             },
             measured_at: now,
         };
@@ -300,6 +794,7 @@ mod tests {
             counters: NetworkCounters {
                 rx_bytes: 100,
                 tx_bytes: 200,
+                ..Default::default()
             },
             measured_at: now,
         };
@@ -307,6 +802,7 @@ mod tests {
             counters: NetworkCounters {
                 rx_bytes: 350,
                 tx_bytes: 700,
+                ..Default::default()
             },
             measured_at: now + Duration::from_secs(5),
         };
@@ -316,6 +812,7 @@ mod tests {
             Some(NetworkRates {
                 rx_bytes_per_second: 50,
                 tx_bytes_per_second: 100,
+                ..Default::default()
             })
         );
     }
@@ -327,6 +824,7 @@ mod tests {
             counters: NetworkCounters {
                 rx_bytes: 100,
                 tx_bytes: 200,
+                ..Default::default()
             },
             measured_at: now,
         };
@@ -334,6 +832,7 @@ mod tests {
             counters: NetworkCounters {
                 rx_bytes: 50,
                 tx_bytes: 100,
+                ..Default::default()
             },
             measured_at: now + Duration::from_secs(5),
         };
@@ -343,6 +842,7 @@ mod tests {
             Some(NetworkRates {
                 rx_bytes_per_second: 0,
                 tx_bytes_per_second: 0,
+                ..Default::default()
             })
         );
     }
@@ -354,6 +854,7 @@ mod tests {
             counters: NetworkCounters {
                 rx_bytes: 0,
                 tx_bytes: 0,
+                ..Default::default()
             },
             measured_at: now,
         };
@@ -361,6 +862,7 @@ mod tests {
             counters: NetworkCounters {
                 rx_bytes: 100,
                 tx_bytes: 50,
+                ..Default::default()
             },
             measured_at: now + Duration::from_secs(2),
         };
@@ -370,23 +872,87 @@ mod tests {
             Some(NetworkRates {
                 rx_bytes_per_second: 50,
                 tx_bytes_per_second: 25,
+                ..Default::default()
             })
         );
     }
 
+    fn rates(rx: u64, tx: u64) -> NetworkRates {
+        NetworkRates {
+            rx_bytes_per_second: rx,
+            tx_bytes_per_second: tx,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn peak_since_start_keeps_highest_rates_per_direction() {
+        let now = Instant::now();
+        let mut peak = PeakRates::new(PeakWindow::Start);
+        assert_eq!(peak.peak(), None);
+
+        peak.observe(now, rates(100, 5));
+        peak.observe(now + Duration::from_secs(5), rates(10, 50));
+        peak.observe(now + 2 * HOUR, rates(1, 1));
+
+        assert_eq!(peak.peak(), Some(rates(100, 50)));
+    }
+
+    #[test]
+    fn peak_in_last_hour_forgets_older_rates() {
+        let now = Instant::now();
+        let mut peak = PeakRates::new(PeakWindow::Hour);
+
+        peak.observe(now, rates(100, 5));
+        peak.observe(now + Duration::from_secs(30 * 60), rates(10, 50));
+        assert_eq!(peak.peak(), Some(rates(100, 50)));
+
+        peak.observe(now + Duration::from_secs(61 * 60), rates(1, 1));
+        assert_eq!(peak.peak(), Some(rates(10, 50)));
+    }
+
+    #[test]
+    fn parses_graph_and_peak_options() {
+        assert_eq!("log".parse::<NetworkGraph>().unwrap(), NetworkGraph::Log);
+        assert_eq!("hour".parse::<PeakWindow>().unwrap(), PeakWindow::Hour);
+        assert!("linear".parse::<NetworkGraph>().is_err());
+        assert!("day".parse::<PeakWindow>().is_err());
+    }
+
     #[test]
     fn format_rate_uses_placeholder_before_calculation() {
-        assert_eq!(format_rate(None), "-.-");
+        assert_eq!(format_rate(None, &BYTE_UNITS, 4), "-.-");
     }
 
     #[test]
     fn format_rate_uses_metric_byte_units() {
-        assert_eq!(format_rate(Some(0)), "0B");
-        assert_eq!(format_rate(Some(999)), "999B");
-        assert_eq!(format_rate(Some(1_500)), "1K");
-        assert_eq!(format_rate(Some(1_500_000)), "1M");
-        assert_eq!(format_rate(Some(999_999)), "1M");
-        assert_eq!(format_rate(Some(u64::MAX)), "18E");
+        assert_eq!(format_rate(Some(0), &BYTE_UNITS, 4), "0B");
+        assert_eq!(format_rate(Some(999), &BYTE_UNITS, 4), "999B");
+        assert_eq!(format_rate(Some(1_500), &BYTE_UNITS, 4), "1K");
+        assert_eq!(format_rate(Some(1_500_000), &BYTE_UNITS, 4), "1M");
+        assert_eq!(format_rate(Some(999_999), &BYTE_UNITS, 4), "1M");
+        assert_eq!(format_rate(Some(u64::MAX), &BYTE_UNITS, 4), "18E");
+    }
+
+    #[test]
+    fn format_rate_fits_narrow_layouts() {
+        assert_eq!(format_rate(None, &BYTE_UNITS, 2), "-");
+        assert_eq!(format_rate(Some(5), &BYTE_UNITS, 2), "5B");
+        assert_eq!(format_rate(Some(999), &BYTE_UNITS, 3), "1K");
+        assert_eq!(format_rate(Some(12_500), &BYTE_UNITS, 3), "12K");
+        assert_eq!(format_rate(Some(12_500), &BYTE_UNITS, 2), "1M");
+    }
+
+    #[test]
+    fn rates_fit_with_largest_font() {
+        let font = FONT_10X20;
+        let character_width = (font.character_size.width + font.character_spacing) as i32;
+        let characters = rate_characters(character_width);
+        let group_width = ARROW_WIDTH + ELEMENT_PADDING + characters * character_width;
+
+        assert_eq!(characters, 2);
+        assert!(2 * group_width + MARK_GAP <= Drawer::WIDTH as i32);
+        assert_eq!(rate_characters(6), RATE_CHARACTERS);
     }
 
     #[test]
@@ -435,14 +1001,173 @@ mod tests {
         write_file(&statistics.join("tx_bytes"), "456\n");
 
         assert_eq!(
-            read_counters(&root.join("eth0")).unwrap(),
+            read_counters(&[root.join("eth0")]).unwrap(),
             NetworkCounters {
                 rx_bytes: 123,
-                tx_bytes: 456
+                tx_bytes: 456,
+                ..Default::default()
             }
         );
     }
 
+    #[test]
+    fn read_counters_adds_up_interfaces() {
+        let root = tempfile_dir("sum");
+        for (name, rx, tx) in [("eth0", "100\n", "10\n"), ("eth1", "23\n", "5\n")] {
+            let statistics = root.join(name).join("statistics");
+            fs::create_dir_all(&statistics).unwrap();
+            write_file(&statistics.join("rx_bytes"), rx);
+            write_file(&statistics.join("tx_bytes"), tx);
+        }
+
+        assert_eq!(
+            read_counters(&[root.join("eth0"), root.join("eth1")]).unwrap(),
+            NetworkCounters {
+                rx_bytes: 123,
+                tx_bytes: 15,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn read_counters_reads_packets_and_faults() {
+        let root = tempfile_dir("faults");
+        let statistics = root.join("eth0").join("statistics");
+        fs::create_dir_all(&statistics).unwrap();
+        for (name, value) in [
+            ("rx_bytes", "123\n"),
+            ("tx_bytes", "456\n"),
+            ("rx_packets", "7\n"),
+            ("tx_packets", "8\n"),
+            ("rx_errors", "1\n"),
+            ("rx_dropped", "2\n"),
+            ("tx_errors", "3\n"),
+        ] {
+            write_file(&statistics.join(name), value);
+        }
+        write_file(&root.join("eth0").join("carrier_changes"), "4\n");
+
+        assert_eq!(
+            read_counters(&[root.join("eth0")]).unwrap(),
+            NetworkCounters {
+                rx_bytes: 123,
+                tx_bytes: 456,
+                rx_packets: 7,
+                tx_packets: 8,
+                faults: 10,
+            }
+        );
+    }
+
+    #[test]
+    fn faults_are_flagged_only_when_they_increase() {
+        let root = tempfile_dir("faulty");
+        let statistics = root.join("eth0").join("statistics");
+        fs::create_dir_all(&statistics).unwrap();
+        write_file(&statistics.join("rx_bytes"), "0\n");
+        write_file(&statistics.join("tx_bytes"), "0\n");
+        write_file(&statistics.join("rx_dropped"), "5\n");
+        let source = NetworkSource {
+            label: "eth0".to_string(),
+            interface_paths: vec![root.join("eth0")],
+        };
+        let mut network = NetworkThroughput::new(vec![source], NetworkOptions::default()).unwrap();
+
+        network.update().unwrap();
+        assert!(!network.interfaces[0].faulty);
+        network.update().unwrap();
+        assert!(!network.interfaces[0].faulty);
+
+        write_file(&statistics.join("rx_dropped"), "6\n");
+        network.update().unwrap();
+        assert!(network.interfaces[0].faulty);
+        network.update().unwrap();
+        assert!(!network.interfaces[0].faulty);
+    }
+
+    #[test]
+    fn packet_rates_are_shown_in_packet_mode() {
+        let rates = NetworkRates {
+            rx_bytes_per_second: 1_500,
+            tx_bytes_per_second: 300,
+            rx_packets_per_second: 12,
+            tx_packets_per_second: 3,
+        };
+
+        assert_eq!(rates.rx_tx(false), (1_500, 300));
+        assert_eq!(rates.rx_tx(true), (12, 3));
+        assert_eq!(format_rate(Some(12), &PACKET_UNITS, 4), "12");
+        assert_eq!(format_rate(Some(12_500), &PACKET_UNITS, 4), "12K");
+        assert_eq!(format_rate(Some(3_000_000_000), &PACKET_UNITS, 4), "3G");
+    }
+
+    #[test]
+    fn packet_mode_adds_label_line() {
+        let source = NetworkSource {
+            label: "eth0".to_string(),
+            interface_paths: vec![PathBuf::from("/nonexistent/eth0")],
+        };
+        let bytes =
+            NetworkThroughput::new(vec![source.clone()], NetworkOptions::default()).unwrap();
+        let packets = NetworkThroughput::new(
+            vec![source],
+            NetworkOptions {
+                packets: true,
+                ..NetworkOptions::default()
+            },
+        )
+        .unwrap();
+
+        assert_eq!(bytes.height(10), 10);
+        assert_eq!(packets.height(10), 20);
+    }
+
+    #[test]
+    fn globs_match_interface_names() {
+        assert!(glob_matches("eth*", "eth0"));
+        assert!(glob_matches("eth*", "eth"));
+        assert!(glob_matches("wg?", "wg0"));
+        assert!(glob_matches("*0", "enp3s0"));
+        assert!(glob_matches("e*p*s0", "enp3s0"));
+        assert!(!glob_matches("wg?", "wg10"));
+        assert!(!glob_matches("eth*", "veth0"));
+    }
+
+    #[test]
+    fn resolves_globs_to_one_source_per_interface() {
+        let root = tempfile_dir("glob");
+        for name in ["eth1", "eth0", "lo", "wg0"] {
+            fs::create_dir(root.join(name)).unwrap();
+        }
+
+        let labels: Vec<String> = NetworkSource::resolve("eth*", &root)
+            .unwrap()
+            .into_iter()
+            .map(|source| source.label)
+            .collect();
+        assert_eq!(labels, vec!["eth0", "eth1"]);
+        assert!(NetworkSource::resolve("bond*", &root).is_err());
+    }
+
+    #[test]
+    fn resolves_physical_interfaces_to_one_source() {
+        let root = tempfile_dir("physical");
+        for name in ["eth0", "eth1", "lo", "br0"] {
+            fs::create_dir(root.join(name)).unwrap();
+        }
+        fs::create_dir(root.join("eth0").join("device")).unwrap();
+        fs::create_dir(root.join("eth1").join("device")).unwrap();
+
+        assert_eq!(
+            NetworkSource::resolve("physical", &root).unwrap(),
+            vec![NetworkSource {
+                label: "physical".to_string(),
+                interface_paths: vec![root.join("eth0"), root.join("eth1")],
+            }]
+        );
+    }
+
     fn tempfile_dir(name: &str) -> PathBuf {
         let root =
             std::env::temp_dir().join(format!("oled-network-test-{}-{}", name, std::process::id()));
@@ -0,0 +1,202 @@
+use super::{Component, Drawer, Error};
+use crate::Threshold;
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use embedded_graphics::{
+    prelude::*,
+    primitives::Line,
+    text::{Baseline, Text},
+};
+
+// number of consecutive updates above the limit before alerting
+const SUSTAINED_SAMPLES: u32 = 3;
+
+#[derive(Clone, Debug)]
+struct Resource {
+    label: &'static str,
+    path: PathBuf,
+    avg10: Option<f32>,
+    threshold: Threshold,
+}
+
+/// Pressure Stall Information for CPU, IO and memory
+#[derive(Clone, Debug)]
+pub struct Pressure {
+    resources: [Resource; 3],
+}
+
+impl Pressure {
+    pub fn new(procfs: &Path, alert_limit: f32) -> Result<Self, Error> {
+        let pressure_root = procfs.join("pressure");
+        let resource = |label, name| -> Result<Resource, Error> {
+            let path = pressure_root.join(name);
+            if !path.is_file() {
+                return Err(format!(
+                    "Could not find {}, is PSI enabled in the kernel?",
+                    path.display()
+                )
+                .into());
+            }
+
+            Ok(Resource {
+                label,
+                path,
+                avg10: None,
+                threshold: Threshold::new(alert_limit, SUSTAINED_SAMPLES),
+            })
+        };
+
+        Ok(Self {
+            resources: [
+                resource("C", "cpu")?,
+                resource("I", "io")?,
+                resource("M", "memory")?,
+            ],
+        })
+    }
+}
+
+impl std::fmt::Display for Pressure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Pressure")
+    }
+}
+
+impl Component for Pressure {
+    fn update_interval(
+        &self,
+        configured: Option<std::time::Duration>,
+    ) -> Option<std::time::Duration> {
+        // avg10 is a ten second average, sampling more often shows nothing new
+        Some(configured.unwrap_or(std::time::Duration::from_secs(10)))
+    }
+
+    fn update(&mut self) -> Result<(), Error> {
+        for resource in &mut self.resources {
+            let avg10 = parse_some_avg10(&fs::read_to_string(&resource.path)?)
+                .ok_or_else(|| format!("Could not parse {}", resource.path.display()))?;
+            resource.threshold.observe(avg10);
+            resource.avg10 = Some(avg10);
+        }
+
+        Ok(())
+    }
+
+    fn alerting(&self) -> bool {
+        self.resources.iter().any(|r| r.threshold.exceeded())
+    }
+
+    fn draw(&self, drawable: &mut Drawer, offset: Point, _tick: u64) -> Result<(), Error> {
+        let group_width = (Drawer::WIDTH as i32 + 1) / self.resources.len() as i32;
+        // bars stand on a base line, leaving a pixel to the line below
+        let base_line = drawable.line_height() as i32 - 3;
+
+        for (i, resource) in self.resources.iter().enumerate() {
+            let group_offset = offset + Point::new(group_width * i as i32, 0);
+            let label = Text::with_baseline(
+                resource.label,
+                group_offset,
+                drawable.base_text_style,
+                Baseline::Top,
+            );
+            let bar_start = label.bounding_box().size.width as i32;
+            label.draw(&mut drawable.display)?;
+
+            let bar_end = group_width - 2;
+            Line::new(
+                group_offset + Point::new(bar_start, base_line),
+                group_offset + Point::new(bar_end, base_line),
+            )
+            .into_styled(drawable.base_primitive_style)
+            .draw(&mut drawable.display)?;
+
+            let Some(avg10) = resource.avg10 else {
+                continue;
+            };
+            let bar_height = ((avg10 / 100.0).clamp(0.0, 1.0) * base_line as f32).round() as i32;
+            for y in 1..=bar_height {
+                Line::new(
+                    group_offset + Point::new(bar_start + 1, base_line - y),
+                    group_offset + Point::new(bar_end, base_line - y),
+                )
+                .into_styled(drawable.base_primitive_style)
+                .draw(&mut drawable.display)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_some_avg10(pressure: &str) -> Option<f32> {
+    pressure
+        .lines()
+        .find_map(|line| line.strip_prefix("some "))?
+        .split_whitespace()
+        .find_map(|field| field.strip_prefix("avg10="))?
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_some_avg10() {
+        let pressure = "some avg10=2.63 avg60=3.03 avg300=2.43 total=13654158
+full avg10=1.00 avg60=0.00 avg300=0.00 total=0
+";
+        assert_eq!(parse_some_avg10(pressure), Some(2.63));
+    }
+
+    #[test]
+    fn ignores_full_line() {
+        assert_eq!(
+            parse_some_avg10("full avg10=1.00 avg60=0.00 avg300=0.00 total=0\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn sustained_pressure_alerts() {
+        let root = std::env::temp_dir().join(format!("oled-pressure-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("pressure")).unwrap();
+        for name in ["cpu", "memory"] {
+            fs::write(
+                root.join("pressure").join(name),
+                "some avg10=0.00 avg60=0.00 avg300=0.00 total=0\n",
+            )
+            .unwrap();
+        }
+        fs::write(
+            root.join("pressure").join("io"),
+            "some avg10=40.00 avg60=0.00 avg300=0.00 total=0\n",
+        )
+        .unwrap();
+
+        let mut pressure = Pressure::new(&root, 25.0).unwrap();
+        for _ in 0..SUSTAINED_SAMPLES {
+            assert!(!pressure.alerting());
+            pressure.update().unwrap();
+        }
+
+        assert!(pressure.alerting());
+        assert_eq!(pressure.resources[1].avg10, Some(40.0));
+    }
+
+    #[test]
+    fn missing_psi_is_an_error() {
+        let root =
+            std::env::temp_dir().join(format!("oled-pressure-test-missing-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+
+        assert!(Pressure::new(&root, 25.0).is_err());
+    }
+}
@@ -0,0 +1,334 @@
+use super::graph::{compact_number, draw_graph, GraphMode, Scale};
+use super::text::draw_text;
+use super::{Component, Drawer, Error, History};
+
+use embedded_graphics::{prelude::*, text::Alignment};
+use nix::sys::socket::{
+    recv, sendto, setsockopt, socket, sockopt, AddressFamily, MsgFlags, SockFlag, SockProtocol,
+    SockType, SockaddrIn,
+};
+use nix::sys::time::{TimeVal, TimeValLike};
+
+use std::{
+    net::{SocketAddr, TcpStream, ToSocketAddrs},
+    os::fd::AsRawFd,
+    time::{Duration, Instant, SystemTime},
+};
+
+const ICMP_ECHO_REQUEST: u8 = 8;
+const ICMP_ECHO_REPLY: u8 = 0;
+
+/// How a target is checked
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ProbeTarget {
+    /// Connecting to `HOST:PORT`
+    Tcp(String),
+    /// An ICMP echo request to a host, if unprivileged ping sockets are
+    /// permitted by `net.ipv4.ping_group_range`
+    Icmp(String),
+}
+
+impl std::str::FromStr for ProbeTarget {
+    type Err = Error;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        if let Some(host) = spec.strip_prefix("icmp:") {
+            if host.is_empty() {
+                return Err("probe host must not be empty".into());
+            }
+            return Ok(Self::Icmp(host.to_string()));
+        }
+
+        let address = spec.strip_prefix("tcp:").unwrap_or(spec);
+        match address.rsplit_once(':') {
+            Some((host, port)) if !host.is_empty() && port.parse::<u16>().is_ok() => {
+                Ok(Self::Tcp(address.to_string()))
+            }
+            _ => Err(format!(
+                "'{}' is not in the format HOST:PORT, tcp:HOST:PORT or icmp:HOST",
+                spec
+            )
+            .into()),
+        }
+    }
+}
+
+impl std::fmt::Display for ProbeTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Tcp(address) => write!(f, "{}", address),
+            Self::Icmp(host) => write!(f, "{}", host),
+        }
+    }
+}
+
+/// Checks whether a target is reachable, showing the round trip time and a
+/// sparkline of it. Failed checks leave gaps in the sparkline.
+#[derive(Clone, Debug)]
+pub struct Probe {
+    target: ProbeTarget,
+    timeout: Duration,
+    interval: Duration,
+    // `None` before the first check
+    latency: Option<Option<Duration>>,
+    history: History,
+}
+
+impl Probe {
+    pub fn new(target: ProbeTarget, interval: Duration, timeout: Duration) -> Self {
+        Self {
+            target,
+            timeout,
+            interval,
+            latency: None,
+            history: History::new(Drawer::WIDTH.into()),
+        }
+    }
+
+    fn check(&self) -> Result<Duration, Error> {
+        match &self.target {
+            ProbeTarget::Tcp(address) => {
+                let address = resolve(address)?;
+                let started = Instant::now();
+                TcpStream::connect_timeout(&address, self.timeout)
+                    .map_err(|e| format!("Could not connect to {}: {}", address, e))?;
+                Ok(started.elapsed())
+            }
+            ProbeTarget::Icmp(host) => {
+                let address = resolve(&format!("{}:0", host))?;
+                let SocketAddr::V4(address) = address else {
+                    return Err(format!("{} has no IPv4 address to ping", host).into());
+                };
+                ping(address.into(), self.timeout)
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Probe {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Probe {}", self.target)
+    }
+}
+
+impl Component for Probe {
+    fn update_interval(&self, configured: Option<Duration>) -> Option<Duration> {
+        Some(configured.unwrap_or(self.interval))
+    }
+
+    fn update(&mut self) -> Result<(), Error> {
+        let result = self.check();
+        self.latency = Some(result.as_ref().ok().copied());
+        if let Ok(latency) = result {
+            self.history
+                .push(SystemTime::now(), latency.as_secs_f32() * 1000.0);
+        }
+        result.map(|_| ())
+    }
+
+    fn height(&self, line_height: u32) -> u32 {
+        2 * line_height
+    }
+
+    fn alerting(&self) -> bool {
+        self.latency == Some(None)
+    }
+
+    fn history(&self) -> Option<&History> {
+        Some(&self.history)
+    }
+
+    fn history_mut(&mut self) -> Option<&mut History> {
+        Some(&mut self.history)
+    }
+
+    fn draw(&self, drawable: &mut Drawer, offset: Point, tick: u64) -> Result<(), Error> {
+        let font = drawable.base_text_style.font;
+        let character_width = font.character_size.width + font.character_spacing;
+        let width = Drawer::WIDTH as u32 + 1;
+        let line_height = drawable.line_height();
+
+        let state = match self.latency {
+            None => "-".to_string(),
+            Some(None) => "down".to_string(),
+            Some(Some(latency)) => format_latency(latency),
+        };
+        // leave room for the state next to the target
+        let target_width = width.saturating_sub((state.len() as u32 + 1) * character_width);
+        draw_text(
+            drawable,
+            &self.target.to_string(),
+            offset,
+            target_width,
+            Alignment::Left,
+            tick,
+        )?;
+        draw_text(drawable, &state, offset, width, Alignment::Right, tick)?;
+
+        draw_graph(
+            drawable,
+            offset + Point::new(0, line_height as i32),
+            Size::new(width, line_height),
+            &self.history,
+            GraphMode::Line,
+            Scale::Auto(compact_number),
+        )
+    }
+}
+
+fn resolve(address: &str) -> Result<SocketAddr, Error> {
+    address
+        .to_socket_addrs()
+        .map_err(|e| format!("Could not resolve {}: {}", address, e))?
+        .next()
+        .ok_or_else(|| format!("{} has no address", address).into())
+}
+
+/// Sends an ICMP echo request to `address` over an unprivileged ping socket
+/// and waits for the reply
+fn ping(address: SockaddrIn, timeout: Duration) -> Result<Duration, Error> {
+    let socket = socket(
+        AddressFamily::Inet,
+        SockType::Datagram,
+        SockFlag::SOCK_CLOEXEC,
+        SockProtocol::Icmp,
+    )
+    .map_err(|e| format!("ICMP is not permitted: {}", e))?;
+    // a zero timeout would wait forever
+    let timeout = timeout.max(Duration::from_millis(1));
+    setsockopt(
+        &socket,
+        sockopt::ReceiveTimeout,
+        &TimeVal::microseconds(timeout.as_micros() as i64),
+    )?;
+
+    // the kernel picks the identifier of ping sockets, the sequence number
+    // tells our reply apart
+    let sequence = std::process::id() as u16;
+    let request = echo_request(sequence);
+    let started = Instant::now();
+    sendto(socket.as_raw_fd(), &request, &address, MsgFlags::empty())?;
+
+    let mut reply = [0u8; 64];
+    while started.elapsed() < timeout {
+        let length = match recv(socket.as_raw_fd(), &mut reply, MsgFlags::empty()) {
+            Ok(length) => length,
+            Err(nix::errno::Errno::EAGAIN) => break,
+            Err(e) => return Err(e.into()),
+        };
+        if length >= 8 && reply[0] == ICMP_ECHO_REPLY && reply[6..8] == sequence.to_be_bytes() {
+            return Ok(started.elapsed());
+        }
+    }
+
+    Err(format!("No ICMP reply from {}", address).into())
+}
+
+fn echo_request(sequence: u16) -> [u8; 12] {
+    let mut request = [0u8; 12];
+    request[0] = ICMP_ECHO_REQUEST;
+    request[6..8].copy_from_slice(&sequence.to_be_bytes());
+    request[8..].copy_from_slice(b"oled");
+    let checksum = checksum(&request);
+    request[2..4].copy_from_slice(&checksum.to_be_bytes());
+    request
+}
+
+/// The internet checksum of `data`, the ones' complement of the ones'
+/// complement sum of its 16 bit words
+fn checksum(data: &[u8]) -> u16 {
+    let mut sum: u32 = data
+        .chunks(2)
+        .map(|word| u16::from_be_bytes([word[0], *word.get(1).unwrap_or(&0)]) as u32)
+        .sum();
+    while sum > 0xffff {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+/// `latency` in at most five characters, like 0.4ms, 12ms or 1.2s
+fn format_latency(latency: Duration) -> String {
+    let milliseconds = latency.as_secs_f32() * 1000.0;
+    match milliseconds {
+        ms if ms < 10.0 => format!("{:.1}ms", ms),
+        ms if ms < 1000.0 => format!("{:.0}ms", ms),
+        ms => format!("{:.1}s", ms / 1000.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    #[test]
+    fn parses_targets() {
+        assert_eq!(
+            "192.168.1.1:22".parse::<ProbeTarget>().unwrap(),
+            ProbeTarget::Tcp("192.168.1.1:22".to_string())
+        );
+        assert_eq!(
+            "tcp:backup.example.com:873".parse::<ProbeTarget>().unwrap(),
+            ProbeTarget::Tcp("backup.example.com:873".to_string())
+        );
+        assert_eq!(
+            "tcp:[::1]:22".parse::<ProbeTarget>().unwrap(),
+            ProbeTarget::Tcp("[::1]:22".to_string())
+        );
+        assert_eq!(
+            "icmp:192.168.1.1".parse::<ProbeTarget>().unwrap(),
+            ProbeTarget::Icmp("192.168.1.1".to_string())
+        );
+        assert!("192.168.1.1".parse::<ProbeTarget>().is_err());
+        assert!("host:ssh".parse::<ProbeTarget>().is_err());
+        assert!("icmp:".parse::<ProbeTarget>().is_err());
+    }
+
+    #[test]
+    fn reaches_local_listener() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let target = ProbeTarget::Tcp(listener.local_addr().unwrap().to_string());
+        let mut probe = Probe::new(target, Duration::from_secs(30), Duration::from_secs(2));
+
+        probe.update().unwrap();
+        assert!(matches!(probe.latency, Some(Some(_))));
+        assert!(!probe.alerting());
+        assert_eq!(probe.history.columns(SystemTime::now()).count(), 1);
+    }
+
+    #[test]
+    fn marks_closed_port_as_down() {
+        // the port is free again once the listener is gone
+        let address = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let mut probe = Probe::new(
+            ProbeTarget::Tcp(address.to_string()),
+            Duration::from_secs(30),
+            Duration::from_secs(2),
+        );
+
+        assert!(probe.update().is_err());
+        assert_eq!(probe.latency, Some(None));
+        assert!(probe.alerting());
+        assert_eq!(probe.history.columns(SystemTime::now()).count(), 0);
+    }
+
+    #[test]
+    fn echo_requests_carry_valid_checksum() {
+        let request = echo_request(0x1234);
+
+        assert_eq!(request[0], ICMP_ECHO_REQUEST);
+        assert_eq!(request[6..8], [0x12, 0x34]);
+        assert_eq!(checksum(&request), 0);
+    }
+
+    #[test]
+    fn formats_latencies() {
+        assert_eq!(format_latency(Duration::from_micros(420)), "0.4ms");
+        assert_eq!(format_latency(Duration::from_millis(12)), "12ms");
+        assert_eq!(format_latency(Duration::from_millis(1300)), "1.3s");
+    }
+}
@@ -0,0 +1,286 @@
+use super::address::interface_addresses;
+use super::network::find_interface_path;
+use super::text::draw_text;
+use super::{Component, Drawer, Error};
+
+use embedded_graphics::{
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+    text::Alignment,
+};
+use qrcodegen::{QrCodeEcc, QrSegment, Version};
+
+use std::{
+    net::IpAddr,
+    path::{Path, PathBuf},
+};
+
+// modules kept free around the code, scanners need some distance to the
+// surrounding components. The spec asks for 4, but that leaves no room for
+// version 3 on this display, and phones cope with 2.
+const QUIET_ZONE: u32 = 2;
+const IP_PLACEHOLDER: &str = "{ip}";
+// larger versions have modules too small to scan on this display
+const MAX_VERSION: u32 = 3;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct EncodedQr {
+    version: u8,
+    scale: u32,
+    size: u32,
+    modules: Vec<bool>,
+}
+
+impl EncodedQr {
+    fn pixel_size(&self) -> u32 {
+        (self.size + 2 * QUIET_ZONE) * self.scale
+    }
+
+    /// Whether the module at `x`, `y` counting from the outer corner of the
+    /// quiet zone is lit. Light modules are, so the code has the regular
+    /// polarity that all scanners read.
+    fn lit(&self, x: u32, y: u32) -> bool {
+        let (Some(x), Some(y)) = (x.checked_sub(QUIET_ZONE), y.checked_sub(QUIET_ZONE)) else {
+            return true;
+        };
+        if x >= self.size || y >= self.size {
+            return true;
+        }
+        !self.modules[(y * self.size + x) as usize]
+    }
+}
+
+/// A QR code of an URL containing the IP address of a network adapter
+#[derive(Clone, Debug)]
+pub struct QrCode {
+    interface_path: PathBuf,
+    template: String,
+    region: u32,
+    url: Option<String>,
+    code: Option<EncodedQr>,
+}
+
+impl QrCode {
+    pub fn new(
+        interface: &str,
+        sysfs_root: &Path,
+        template: String,
+        region: u32,
+    ) -> Result<Self, Error> {
+        if region > Drawer::WIDTH as u32 + 1 {
+            return Err(format!(
+                "QR code size must not be larger than {}px",
+                Drawer::WIDTH as u32 + 1
+            )
+            .into());
+        }
+
+        Ok(Self {
+            interface_path: find_interface_path(sysfs_root, interface)?,
+            template,
+            region,
+            url: None,
+            code: None,
+        })
+    }
+}
+
+impl std::fmt::Display for QrCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "QR code")
+    }
+}
+
+impl Component for QrCode {
+    fn update_interval(
+        &self,
+        configured: Option<std::time::Duration>,
+    ) -> Option<std::time::Duration> {
+        Some(configured.unwrap_or(std::time::Duration::from_secs(5)))
+    }
+
+    fn update(&mut self) -> Result<(), Error> {
+        let interface_name = self
+            .interface_path
+            .file_name()
+            .ok_or("Could not get network adapter name")?
+            .to_string_lossy()
+            .to_string();
+        let url = interface_addresses(&interface_name, true)?
+            .first()
+            .map(|ip| render_url(&self.template, *ip));
+
+        if url != self.url {
+            self.code = match &url {
+                Some(url) => Some(
+                    encode(url, self.region)
+                        .ok_or_else(|| format!("'{}' is too long for a QR code", url))?,
+                ),
+                None => None,
+            };
+            self.url = url;
+        }
+
+        Ok(())
+    }
+
+    fn height(&self, _line_height: u32) -> u32 {
+        self.region
+    }
+
+    fn draw(&self, drawable: &mut Drawer, offset: Point, tick: u64) -> Result<(), Error> {
+        let Some(code) = &self.code else {
+            return draw_text(
+                drawable,
+                "no address",
+                offset,
+                Drawer::WIDTH as u32 + 1,
+                Alignment::Center,
+                tick,
+            );
+        };
+
+        let left = (Drawer::WIDTH as u32 + 1).saturating_sub(code.pixel_size()) / 2;
+        let origin = offset + Point::new(left as i32, 0);
+        let style = PrimitiveStyle::with_fill(
+            drawable
+                .base_primitive_style
+                .stroke_color
+                .ok_or("no drawing color")?,
+        );
+
+        let modules = code.size + 2 * QUIET_ZONE;
+        for (x, y) in (0..modules)
+            .flat_map(|y| (0..modules).map(move |x| (x, y)))
+            .filter(|(x, y)| code.lit(*x, *y))
+        {
+            Rectangle::new(
+                origin + Point::new((x * code.scale) as i32, (y * code.scale) as i32),
+                Size::new(code.scale, code.scale),
+            )
+            .into_styled(style)
+            .draw(&mut drawable.display)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn render_url(template: &str, ip: IpAddr) -> String {
+    let ip = match ip {
+        IpAddr::V4(v4) => v4.to_string(),
+        IpAddr::V6(v6) => format!("[{}]", v6),
+    };
+    template.replace(IP_PLACEHOLDER, &ip)
+}
+
+/// Encodes `text` with the largest version (and thereby the most error
+/// correction) fitting into a square of `region` pixels, and within that
+/// version with the biggest modules.
+fn encode(text: &str, region: u32) -> Option<EncodedQr> {
+    let segments = QrSegment::make_segments(text);
+
+    for version in (1..=MAX_VERSION).rev() {
+        let size = 17 + 4 * version;
+        let scale = region / (size + 2 * QUIET_ZONE);
+        if scale == 0 {
+            continue;
+        }
+
+        let Ok(code) = qrcodegen::QrCode::encode_segments_advanced(
+            &segments,
+            QrCodeEcc::Low,
+            Version::new(version as u8),
+            Version::new(version as u8),
+            None,
+            true, // use the highest error correction the version allows
+        ) else {
+            continue;
+        };
+
+        let size = code.size();
+        return Some(EncodedQr {
+            version: version as u8,
+            scale,
+            size: size as u32,
+            modules: (0..size)
+                .flat_map(|y| (0..size).map(move |x| (x, y)))
+                .map(|(x, y)| code.get_module(x, y))
+                .collect(),
+        });
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    #[test]
+    fn renders_ip_into_url() {
+        assert_eq!(
+            render_url(
+                "http://{ip}:8080/",
+                IpAddr::V4(Ipv4Addr::new(192, 168, 1, 23))
+            ),
+            "http://192.168.1.23:8080/"
+        );
+        assert_eq!(
+            render_url("http://{ip}/", IpAddr::V6(Ipv6Addr::LOCALHOST)),
+            "http://[::1]/"
+        );
+    }
+
+    #[test]
+    fn prefers_larger_versions() {
+        let code = encode("http://192.168.1.23/", 60).unwrap();
+
+        assert_eq!(code.version, 3);
+        assert_eq!(code.scale, 1);
+        assert_eq!(code.size, 29);
+        assert_eq!(code.modules.len(), 29 * 29);
+        assert_eq!(code.pixel_size(), 33);
+    }
+
+    #[test]
+    fn picks_largest_version_for_configurable_regions() {
+        let code = encode("http://10.0.0.1/", Drawer::WIDTH as u32 + 1).unwrap();
+        assert_eq!((code.version, code.scale), (3, 1));
+
+        // a pixel short of version 3 with its quiet zone
+        let code = encode("http://10.0.0.1/", 32).unwrap();
+        assert_eq!((code.version, code.scale), (2, 1));
+    }
+
+    #[test]
+    fn falls_back_to_smaller_versions_for_small_regions() {
+        let code = encode("http://10.0.0.1/", 25).unwrap();
+
+        assert_eq!(code.version, 1);
+        assert_eq!(code.scale, 1);
+    }
+
+    #[test]
+    fn lights_light_modules_and_quiet_zone() {
+        let code = encode("http://192.168.1.23/", 60).unwrap();
+
+        // quiet zone
+        assert!(code.lit(0, 0));
+        assert!(code.lit(code.size + QUIET_ZONE, code.size + QUIET_ZONE));
+        // the top left finder pattern: dark border, light ring, dark center
+        assert!(!code.lit(QUIET_ZONE, QUIET_ZONE));
+        assert!(code.lit(QUIET_ZONE + 1, QUIET_ZONE + 1));
+        assert!(!code.lit(QUIET_ZONE + 3, QUIET_ZONE + 3));
+        // the separator next to it
+        assert!(code.lit(QUIET_ZONE + 7, QUIET_ZONE));
+    }
+
+    #[test]
+    fn rejects_text_that_does_not_fit() {
+        assert_eq!(encode(&"x".repeat(60), 60), None);
+        assert_eq!(encode("http://10.0.0.1/", 20), None);
+    }
+}
@@ -0,0 +1,407 @@
+use super::text::draw_text;
+use super::{Component, Drawer, Error};
+
+use embedded_graphics::{prelude::*, text::Alignment};
+use nix::poll::{poll, PollFd, PollFlags};
+use nix::sys::inotify::{AddWatchFlags, InitFlags, Inotify, WatchDescriptor};
+
+use std::{
+    ffi::OsString,
+    fs,
+    os::fd::AsFd,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+// how long an update waits for changes, well within --update-timeout
+const EVENT_WAIT_MS: u16 = 1000;
+
+/// A status file as given on the command line, `PATH` or `PATH:KEY`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StatusFileSpec {
+    pub path: PathBuf,
+    pub key: Option<String>,
+}
+
+impl std::str::FromStr for StatusFileSpec {
+    type Err = Error;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let (path, key) = match spec.rsplit_once(':') {
+            Some((path, key)) if !key.contains('/') => (path, Some(key.to_string())),
+            _ => (spec, None),
+        };
+
+        if path.is_empty() {
+            return Err("status file path must not be empty".into());
+        }
+
+        Ok(Self {
+            path: PathBuf::from(path),
+            key,
+        })
+    }
+}
+
+/// Shows the first line, or a key of a JSON object, of a file written by
+/// another tool
+#[derive(Clone, Debug)]
+pub struct StatusFile {
+    spec: StatusFileSpec,
+    file_name: OsString,
+    parent: PathBuf,
+    max_age: Option<Duration>,
+    inotify: Arc<Inotify>,
+    // the watch of the parent directory, gone when it was moved or deleted
+    watch: Option<WatchDescriptor>,
+    loaded: bool,
+    value: Option<String>,
+    failed: bool,
+    stale: bool,
+}
+
+impl StatusFile {
+    pub fn new(spec: StatusFileSpec, max_age: Option<Duration>) -> Result<Self, Error> {
+        let file_name = spec
+            .path
+            .file_name()
+            .ok_or_else(|| format!("{} is not a file", spec.path.display()))?
+            .to_os_string();
+        let parent = match spec.path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+
+        let mut status_file = Self {
+            spec,
+            file_name,
+            parent,
+            max_age,
+            inotify: Arc::new(Inotify::init(
+                InitFlags::IN_NONBLOCK | InitFlags::IN_CLOEXEC,
+            )?),
+            watch: None,
+            loaded: false,
+            value: None,
+            failed: false,
+            stale: false,
+        };
+        status_file.add_watch()?;
+        Ok(status_file)
+    }
+
+    /// Watches the parent directory, tools often replace status files by
+    /// renaming a new file over them, which a watch of the file itself
+    /// wouldn't survive
+    fn add_watch(&mut self) -> Result<(), Error> {
+        let watch = self
+            .inotify
+            .add_watch(
+                &self.parent,
+                AddWatchFlags::IN_CLOSE_WRITE
+                    | AddWatchFlags::IN_MOVED_TO
+                    | AddWatchFlags::IN_CREATE
+                    | AddWatchFlags::IN_DELETE
+                    | AddWatchFlags::IN_DELETE_SELF
+                    | AddWatchFlags::IN_MOVE_SELF,
+            )
+            .map_err(|e| format!("Could not watch {}: {}", self.parent.display(), e))?;
+        self.watch = Some(watch);
+        Ok(())
+    }
+
+    /// Waits a moment for changes to the file, returning whether there were
+    /// any or events might have been missed
+    fn changed(&mut self) -> Result<bool, Error> {
+        if self.watch.is_none() {
+            // the directory is back, or this fails again and we retry later
+            self.add_watch()?;
+            return Ok(true);
+        }
+
+        let mut fds = [PollFd::new(self.inotify.as_fd(), PollFlags::POLLIN)];
+        if poll(&mut fds, EVENT_WAIT_MS)? == 0 {
+            return Ok(false);
+        }
+        let events = match self.inotify.read_events() {
+            Ok(events) => events,
+            Err(nix::errno::Errno::EAGAIN) => return Ok(false),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut changed = false;
+        for event in events {
+            if event.mask.contains(AddWatchFlags::IN_Q_OVERFLOW) {
+                changed = true;
+            }
+            if Some(event.wd) == self.watch
+                && event.mask.intersects(
+                    AddWatchFlags::IN_IGNORED
+                        | AddWatchFlags::IN_DELETE_SELF
+                        | AddWatchFlags::IN_MOVE_SELF,
+                )
+            {
+                // a moved directory is still watched under its new name
+                let _ = self.inotify.rm_watch(event.wd);
+                self.watch = None;
+                changed = true;
+            }
+            if event.name.as_ref() == Some(&self.file_name) {
+                changed = true;
+            }
+        }
+
+        if self.watch.is_none() {
+            self.add_watch()?;
+        }
+        Ok(changed)
+    }
+
+    fn reload(&mut self) -> Result<(), Error> {
+        let content = fs::read_to_string(&self.spec.path)
+            .map_err(|e| format!("Could not read {}: {}", self.spec.path.display(), e))?;
+        self.value = Some(extract_value(&content, self.spec.key.as_deref())?);
+        Ok(())
+    }
+
+    fn modified_age(&self) -> Result<Duration, Error> {
+        let modified = fs::metadata(&self.spec.path)?.modified()?;
+        // a modification time in the future is as fresh as it gets
+        Ok(SystemTime::now()
+            .duration_since(modified)
+            .unwrap_or(Duration::ZERO))
+    }
+}
+
+impl std::fmt::Display for StatusFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Status file {}", self.spec.path.display())
+    }
+}
+
+impl Component for StatusFile {
+    fn update_interval(&self, configured: Option<Duration>) -> Option<Duration> {
+        // updates wait for changes themselves
+        Some(configured.unwrap_or(Duration::ZERO))
+    }
+
+    fn update(&mut self) -> Result<(), Error> {
+        if !self.loaded || self.changed()? {
+            self.loaded = true;
+            let result = self.reload();
+            self.failed = result.is_err();
+            result?;
+        }
+
+        if let (Some(max_age), false) = (self.max_age, self.failed) {
+            self.stale = self.modified_age()? > max_age;
+        }
+        Ok(())
+    }
+
+    fn draw(&self, drawable: &mut Drawer, offset: Point, tick: u64) -> Result<(), Error> {
+        let marker = match (self.failed, self.stale) {
+            (true, _) => Some("!"),
+            (false, true) => Some("?"),
+            (false, false) => None,
+        };
+
+        let font = drawable.base_text_style.font;
+        let character_width = font.character_size.width + font.character_spacing;
+        let width = match marker {
+            // leave room for the marker and a pixel of space
+            Some(_) => Drawer::WIDTH as u32 - character_width,
+            None => Drawer::WIDTH as u32 + 1,
+        };
+        draw_text(
+            drawable,
+            self.value.as_deref().unwrap_or("-"),
+            offset,
+            width,
+            Alignment::Left,
+            tick,
+        )?;
+
+        if let Some(marker) = marker {
+            draw_text(
+                drawable,
+                marker,
+                offset,
+                Drawer::WIDTH as u32 + 1,
+                Alignment::Right,
+                tick,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+fn extract_value(content: &str, key: Option<&str>) -> Result<String, Error> {
+    let Some(key) = key else {
+        return Ok(content.lines().next().unwrap_or("").trim().to_string());
+    };
+
+    let object: serde_json::Value =
+        serde_json::from_str(content).map_err(|e| format!("Could not parse JSON: {}", e))?;
+    match object.get(key) {
+        Some(serde_json::Value::String(value)) => Ok(value.clone()),
+        Some(value) => Ok(value.to_string()),
+        None => Err(format!("JSON object has no key '{}'", key).into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tempfile_dir(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!(
+            "oled-status-file-test-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir(&root).unwrap();
+        root
+    }
+
+    #[test]
+    fn parses_spec() {
+        assert_eq!(
+            "/run/backup/status.json:state"
+                .parse::<StatusFileSpec>()
+                .unwrap(),
+            StatusFileSpec {
+                path: PathBuf::from("/run/backup/status.json"),
+                key: Some("state".to_string()),
+            }
+        );
+        assert_eq!(
+            "/run/deploy".parse::<StatusFileSpec>().unwrap(),
+            StatusFileSpec {
+                path: PathBuf::from("/run/deploy"),
+                key: None,
+            }
+        );
+        assert!("".parse::<StatusFileSpec>().is_err());
+    }
+
+    #[test]
+    fn extracts_first_line() {
+        assert_eq!(
+            extract_value("  backup ok \nsecond line\n", None).unwrap(),
+            "backup ok"
+        );
+        assert_eq!(extract_value("", None).unwrap(), "");
+    }
+
+    #[test]
+    fn extracts_json_keys() {
+        let content = r#"{"state": "running", "progress": 42}"#;
+
+        assert_eq!(extract_value(content, Some("state")).unwrap(), "running");
+        assert_eq!(extract_value(content, Some("progress")).unwrap(), "42");
+        assert!(extract_value(content, Some("missing")).is_err());
+        assert!(extract_value("not json", Some("state")).is_err());
+    }
+
+    #[test]
+    fn reloads_on_change() {
+        let root = tempfile_dir("reload");
+        let path = root.join("status");
+        fs::write(&path, "first\n").unwrap();
+
+        let mut status = StatusFile::new(
+            StatusFileSpec {
+                path: path.clone(),
+                key: None,
+            },
+            None,
+        )
+        .unwrap();
+        status.update().unwrap();
+        assert_eq!(status.value.as_deref(), Some("first"));
+
+        // replace the file like most tools do
+        fs::write(root.join("status.tmp"), "second\n").unwrap();
+        fs::rename(root.join("status.tmp"), &path).unwrap();
+        status.update().unwrap();
+        assert_eq!(status.value.as_deref(), Some("second"));
+    }
+
+    #[test]
+    fn keeps_watching_replaced_directories() {
+        let root = tempfile_dir("replaced");
+        let path = root.join("status");
+        fs::write(&path, "first\n").unwrap();
+
+        let mut status = StatusFile::new(
+            StatusFileSpec {
+                path: path.clone(),
+                key: None,
+            },
+            None,
+        )
+        .unwrap();
+        status.update().unwrap();
+
+        // like a deployment swapping in a new directory
+        let old = tempfile_dir("replaced-old");
+        fs::remove_dir(&old).unwrap();
+        fs::rename(&root, &old).unwrap();
+        fs::create_dir(&root).unwrap();
+        fs::write(&path, "second\n").unwrap();
+        status.update().unwrap();
+        assert_eq!(status.value.as_deref(), Some("second"));
+
+        fs::write(root.join("status.tmp"), "third\n").unwrap();
+        fs::rename(root.join("status.tmp"), &path).unwrap();
+        status.update().unwrap();
+        assert_eq!(status.value.as_deref(), Some("third"));
+    }
+
+    #[test]
+    fn marks_missing_file_as_failed() {
+        let root = tempfile_dir("missing");
+        let mut status = StatusFile::new(
+            StatusFileSpec {
+                path: root.join("status"),
+                key: None,
+            },
+            None,
+        )
+        .unwrap();
+
+        assert!(status.update().is_err());
+        assert!(status.failed);
+    }
+
+    #[test]
+    fn marks_old_file_as_stale() {
+        let root = tempfile_dir("stale");
+        let path = root.join("status");
+        fs::write(&path, "done\n").unwrap();
+
+        let mut status = StatusFile::new(
+            StatusFileSpec {
+                path: path.clone(),
+                key: None,
+            },
+            Some(Duration::from_secs(3600)),
+        )
+        .unwrap();
+        status.update().unwrap();
+        assert!(!status.stale);
+
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(SystemTime::now() - Duration::from_secs(7200))
+            .unwrap();
+        status.update().unwrap();
+        assert!(status.stale);
+    }
+}
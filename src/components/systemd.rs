@@ -0,0 +1,259 @@
+use super::text::draw_text;
+use super::{Component, Drawer, Error};
+
+use embedded_graphics::{prelude::*, text::Alignment};
+
+use std::time::Duration;
+
+/// The state of a configured unit, as reported by `systemctl show`
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct UnitState {
+    name: String,
+    load_state: String,
+    active_state: String,
+}
+
+impl UnitState {
+    fn failed(&self) -> bool {
+        self.active_state == "failed"
+    }
+
+    /// The state in a few characters
+    fn short_state(&self) -> &'static str {
+        if self.load_state == "not-found" {
+            return "?";
+        }
+        match self.active_state.as_str() {
+            "active" => "ok",
+            "inactive" => "off",
+            "failed" => "fail",
+            "activating" | "deactivating" | "reloading" | "refreshing" => "..",
+            _ => "?",
+        }
+    }
+}
+
+/// The number of failed systemd units and the state of configured units.
+/// Failed units make the component alert.
+#[derive(Clone, Debug)]
+pub struct Systemd {
+    units: Vec<String>,
+    failed: Option<usize>,
+    states: Vec<UnitState>,
+}
+
+impl Systemd {
+    pub fn new(units: Vec<String>) -> Self {
+        Self {
+            units,
+            failed: None,
+            states: Vec::new(),
+        }
+    }
+
+    /// The output of `list-units --failed` and of `show` for the configured
+    /// units
+    fn query(&self) -> Result<(String, String), Error> {
+        let failed = systemctl(&["list-units", "--failed", "--output=json"])?;
+        if self.units.is_empty() {
+            return Ok((failed, String::new()));
+        }
+
+        let mut args = vec!["show", "--property=Id,LoadState,ActiveState", "--"];
+        args.extend(self.units.iter().map(String::as_str));
+        Ok((failed, systemctl(&args)?))
+    }
+
+    /// Takes the failed units from `list-units --failed --output=json` and
+    /// the configured units' states from `show`
+    fn load(&mut self, failed: &str, show: &str) -> Result<(), Error> {
+        self.failed = Some(parse_failed_units(failed)?);
+        self.states = parse_unit_states(show)?;
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for Systemd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Systemd")
+    }
+}
+
+impl Component for Systemd {
+    fn update_interval(&self, configured: Option<Duration>) -> Option<Duration> {
+        Some(configured.unwrap_or(Duration::from_secs(30)))
+    }
+
+    fn update(&mut self) -> Result<(), Error> {
+        let result = self
+            .query()
+            .and_then(|(failed, show)| self.load(&failed, &show));
+        if result.is_err() {
+            self.failed = None;
+            self.states.clear();
+        }
+        result
+    }
+
+    fn height(&self, line_height: u32) -> u32 {
+        line_height * (1 + self.states.len() as u32)
+    }
+
+    fn alerting(&self) -> bool {
+        self.failed.is_some_and(|failed| failed > 0) || self.states.iter().any(UnitState::failed)
+    }
+
+    fn draw(&self, drawable: &mut Drawer, offset: Point, tick: u64) -> Result<(), Error> {
+        let font = drawable.base_text_style.font;
+        let character_width = font.character_size.width + font.character_spacing;
+        let width = Drawer::WIDTH as u32 + 1;
+        let line_height = drawable.line_height() as i32;
+
+        let failed = match self.failed {
+            Some(failed) => failed.to_string(),
+            None => "!".to_string(),
+        };
+        draw_text(drawable, "Failed", offset, width, Alignment::Left, tick)?;
+        draw_text(drawable, &failed, offset, width, Alignment::Right, tick)?;
+
+        for (i, unit) in self.states.iter().enumerate() {
+            let row = offset + Point::new(0, line_height * (i as i32 + 1));
+            let state = unit.short_state();
+            // leave room for the state next to the name
+            let name_width = width.saturating_sub((state.len() as u32 + 1) * character_width);
+            let name = unit.name.strip_suffix(".service").unwrap_or(&unit.name);
+
+            draw_text(drawable, name, row, name_width, Alignment::Left, tick)?;
+            draw_text(drawable, state, row, width, Alignment::Right, tick)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn systemctl(args: &[&str]) -> Result<String, Error> {
+    let output = std::process::Command::new("systemctl")
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .output()
+        .map_err(|e| format!("Could not run systemctl: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "systemctl {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// The number of units in the JSON array of `list-units --output=json`
+fn parse_failed_units(json: &str) -> Result<usize, Error> {
+    let units: serde_json::Value =
+        serde_json::from_str(json).map_err(|e| format!("Could not parse JSON: {}", e))?;
+    match units {
+        serde_json::Value::Array(units) => Ok(units.len()),
+        _ => Err("systemctl did not list units as a JSON array".into()),
+    }
+}
+
+/// The units in the output of `show`, blocks of `PROPERTY=VALUE` lines
+/// separated by empty lines
+fn parse_unit_states(show: &str) -> Result<Vec<UnitState>, Error> {
+    show.split("\n\n")
+        .filter(|block| !block.trim().is_empty())
+        .map(|block| {
+            let property = |name: &str| {
+                block
+                    .lines()
+                    .find_map(|line| line.strip_prefix(name)?.strip_prefix('='))
+                    .map(str::to_string)
+                    .ok_or_else(|| format!("systemctl show is missing {}", name))
+            };
+
+            Ok(UnitState {
+                name: property("Id")?,
+                load_state: property("LoadState")?,
+                active_state: property("ActiveState")?,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FAILED: &str = r#"[{"unit":"backup.service","load":"loaded","active":"failed","sub":"failed","description":"Nightly backup"}]"#;
+
+    const SHOW: &str = "\
+Id=smbd.service
+LoadState=loaded
+ActiveState=active
+
+Id=nfs-server.service
+LoadState=loaded
+ActiveState=activating
+
+Id=backup.service
+LoadState=loaded
+ActiveState=failed
+
+Id=gone.service
+LoadState=not-found
+ActiveState=inactive
+";
+
+    #[test]
+    fn counts_failed_units() {
+        assert_eq!(parse_failed_units(FAILED).unwrap(), 1);
+        assert_eq!(parse_failed_units("[]\n").unwrap(), 0);
+        assert!(parse_failed_units("{}").is_err());
+        assert!(parse_failed_units("UNIT LOAD ACTIVE").is_err());
+    }
+
+    #[test]
+    fn parses_unit_states() {
+        let states = parse_unit_states(SHOW).unwrap();
+
+        assert_eq!(
+            states[0],
+            UnitState {
+                name: "smbd.service".to_string(),
+                load_state: "loaded".to_string(),
+                active_state: "active".to_string(),
+            }
+        );
+        let short: Vec<&str> = states.iter().map(UnitState::short_state).collect();
+        assert_eq!(short, vec!["ok", "..", "fail", "?"]);
+        assert!(parse_unit_states("Id=smbd.service\n").is_err());
+        assert_eq!(parse_unit_states("").unwrap(), vec![]);
+    }
+
+    #[test]
+    fn alerts_on_failed_units() {
+        let mut systemd = Systemd::new(vec!["smbd.service".to_string()]);
+        systemd
+            .load(
+                "[]",
+                "Id=smbd.service\nLoadState=loaded\nActiveState=active\n",
+            )
+            .unwrap();
+        assert!(!systemd.alerting());
+        assert_eq!(systemd.height(10), 20);
+
+        // failing anywhere on the system
+        systemd
+            .load(
+                FAILED,
+                "Id=smbd.service\nLoadState=loaded\nActiveState=active\n",
+            )
+            .unwrap();
+        assert!(systemd.alerting());
+
+        // a configured unit that failed
+        systemd.load("[]", SHOW).unwrap();
+        assert!(systemd.alerting());
+    }
+}
@@ -0,0 +1,84 @@
+use super::{Drawer, Error};
+
+use embedded_graphics::{
+    prelude::*,
+    primitives::Rectangle,
+    text::{Alignment, Baseline, Text},
+};
+
+// blank characters between the end of scrolling text and its repetition
+const SCROLL_GAP: usize = 3;
+// ticks the start of scrolling text stays in place, so it can be read
+const SCROLL_PAUSE: u64 = 2;
+
+/// Draws a line of `text` clipped to `width` pixels. Text that fits is
+/// aligned within `width`, text that doesn't fit scrolls to the left by one
+/// character per tick and wraps around.
+pub fn draw_text(
+    drawable: &mut Drawer,
+    text: &str,
+    offset: Point,
+    width: u32,
+    alignment: Alignment,
+    tick: u64,
+) -> Result<(), Error> {
+    let style = drawable.base_text_style;
+    let character_width = style.font.character_size.width + style.font.character_spacing;
+    let length = text.chars().count();
+    let text_width = (length as u32 * character_width).saturating_sub(style.font.character_spacing);
+    let area = Rectangle::new(offset, Size::new(width, style.font.character_size.height));
+    let mut display = drawable.display.clipped(&area);
+
+    if text_width <= width {
+        let start = match alignment {
+            Alignment::Left => 0,
+            Alignment::Center => (width - text_width) / 2,
+            Alignment::Right => width - text_width,
+        };
+        Text::with_baseline(
+            text,
+            offset + Point::new(start as i32, 0),
+            style,
+            Baseline::Top,
+        )
+        .draw(&mut display)?;
+        return Ok(());
+    }
+
+    let shift = scroll_shift(length, tick) as i32 * character_width as i32;
+    let repetition = (length + SCROLL_GAP) as i32 * character_width as i32;
+
+    for start in [-shift, repetition - shift] {
+        Text::with_baseline(text, offset + Point::new(start, 0), style, Baseline::Top)
+            .draw(&mut display)?;
+    }
+
+    Ok(())
+}
+
+/// How many characters scrolling text of `length` characters is shifted to
+/// the left at `tick`.
+fn scroll_shift(length: usize, tick: u64) -> u64 {
+    let characters = (length + SCROLL_GAP) as u64;
+    (tick % (characters + SCROLL_PAUSE)).saturating_sub(SCROLL_PAUSE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scrolling_pauses_at_the_start() {
+        assert_eq!(scroll_shift(10, 0), 0);
+        assert_eq!(scroll_shift(10, SCROLL_PAUSE), 0);
+        assert_eq!(scroll_shift(10, SCROLL_PAUSE + 1), 1);
+    }
+
+    #[test]
+    fn scrolling_wraps_around_after_the_gap() {
+        let period = 10 + SCROLL_GAP as u64 + SCROLL_PAUSE;
+
+        assert_eq!(scroll_shift(10, period - 1), 10 + SCROLL_GAP as u64 - 1);
+        assert_eq!(scroll_shift(10, period), 0);
+    }
+}
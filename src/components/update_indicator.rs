@@ -6,7 +6,7 @@ use embedded_graphics::{
     prelude::*,
 };
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct UpdateIndicator {}
 
 impl std::fmt::Display for UpdateIndicator {
@@ -16,8 +16,11 @@ impl std::fmt::Display for UpdateIndicator {
 }
 
 impl Component for UpdateIndicator {
-    fn should_update(&self, _last_update: std::time::Duration) -> bool {
-        false
+    fn update_interval(
+        &self,
+        _configured: Option<std::time::Duration>,
+    ) -> Option<std::time::Duration> {
+        None
     }
 
     fn update(&mut self) -> Result<(), Error> {
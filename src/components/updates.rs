@@ -0,0 +1,209 @@
+use super::text::draw_text;
+use super::{Component, Drawer, Error};
+
+use embedded_graphics::{
+    pixelcolor::BinaryColor,
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+    text::Alignment,
+};
+
+use std::{fs, path::PathBuf, time::Duration};
+
+/// Upgradable packages counted by a periodic job or apt hook and whether a
+/// reboot is required, like after a kernel update
+#[derive(Clone, Debug)]
+pub struct Updates {
+    updates_file: PathBuf,
+    reboot_required_file: PathBuf,
+    updates: Option<usize>,
+    reboot_required: bool,
+}
+
+impl Updates {
+    pub fn new(updates_file: PathBuf, reboot_required_file: PathBuf) -> Self {
+        Self {
+            updates_file,
+            reboot_required_file,
+            updates: None,
+            reboot_required: false,
+        }
+    }
+}
+
+impl std::fmt::Display for Updates {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Updates")
+    }
+}
+
+impl Component for Updates {
+    fn update_interval(&self, configured: Option<Duration>) -> Option<Duration> {
+        Some(configured.unwrap_or(Duration::from_secs(5 * 60)))
+    }
+
+    fn update(&mut self) -> Result<(), Error> {
+        self.reboot_required = self.reboot_required_file.exists();
+
+        let result = fs::read_to_string(&self.updates_file)
+            .map_err(|e| format!("Could not read {}: {}", self.updates_file.display(), e).into())
+            .and_then(|content| parse_updates(&content));
+        self.updates = result.as_ref().ok().copied();
+        result.map(|_| ())
+    }
+
+    fn height(&self, line_height: u32) -> u32 {
+        match self.reboot_required {
+            true => 2 * line_height,
+            false => line_height,
+        }
+    }
+
+    fn draw(&self, drawable: &mut Drawer, offset: Point, tick: u64) -> Result<(), Error> {
+        let width = Drawer::WIDTH as u32 + 1;
+        let updates = match self.updates {
+            Some(updates) => updates.to_string(),
+            None => "!".to_string(),
+        };
+        draw_text(drawable, "Updates", offset, width, Alignment::Left, tick)?;
+        draw_text(drawable, &updates, offset, width, Alignment::Right, tick)?;
+
+        if self.reboot_required {
+            let line_height = drawable.line_height();
+            let badge = offset + Point::new(0, line_height as i32);
+            draw_badge(
+                drawable,
+                "reboot",
+                badge,
+                Size::new(width, line_height),
+                tick,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// `text` centered in a filled box, with the colors swapped
+fn draw_badge(
+    drawable: &mut Drawer,
+    text: &str,
+    offset: Point,
+    size: Size,
+    tick: u64,
+) -> Result<(), Error> {
+    let foreground = drawable
+        .base_text_style
+        .text_color
+        .unwrap_or(BinaryColor::On);
+    Rectangle::new(offset, size)
+        .into_styled(PrimitiveStyle::with_fill(foreground))
+        .draw(&mut drawable.display)?;
+
+    let text_color = drawable.base_text_style.text_color;
+    drawable.base_text_style.text_color = Some(foreground.invert());
+    let result = draw_text(drawable, text, offset, size.width, Alignment::Center, tick);
+    drawable.base_text_style.text_color = text_color;
+    result
+}
+
+/// The number of upgradable packages in `content`. It is either the
+/// `UPDATES;SECURITY_UPDATES` that `apt-check` prints, a plain number like
+/// from `apt list --upgradable | grep -c upgradable`, or the message that
+/// update-notifier caches, like `12 updates can be applied immediately.`
+fn parse_updates(content: &str) -> Result<usize, Error> {
+    let content = content.trim();
+    if content.is_empty() {
+        return Err("Update count is empty".into());
+    }
+
+    if let Some((updates, _security)) = content.split_once(';') {
+        return updates
+            .trim()
+            .parse()
+            .map_err(|_| format!("'{}' is not in the apt-check format", content).into());
+    }
+    if let Ok(updates) = content.parse() {
+        return Ok(updates);
+    }
+
+    // update-notifier only mentions security updates in a second line
+    let Some(line) = content.lines().find(|line| line.contains("can be")) else {
+        return Err(format!("'{}' has no update count", content).into());
+    };
+    line.split_whitespace()
+        .next()
+        .and_then(|updates| updates.parse().ok())
+        .ok_or_else(|| format!("'{}' has no update count", line).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UPDATES_AVAILABLE: &str = "
+12 updates can be applied immediately.
+3 of these updates are standard security updates.
+To see these additional updates run: apt list --upgradable
+
+";
+
+    fn tempfile_dir(name: &str) -> PathBuf {
+        let root =
+            std::env::temp_dir().join(format!("oled-updates-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir(&root).unwrap();
+        root
+    }
+
+    #[test]
+    fn parses_update_counts() {
+        assert_eq!(parse_updates("12;3").unwrap(), 12);
+        assert_eq!(parse_updates("7\n").unwrap(), 7);
+        assert_eq!(parse_updates(UPDATES_AVAILABLE).unwrap(), 12);
+        assert_eq!(
+            parse_updates("0 updates can be applied immediately.\n").unwrap(),
+            0
+        );
+        assert!(
+            parse_updates("Expanded Security Maintenance for Applications is not enabled.\n")
+                .is_err()
+        );
+        assert!(parse_updates("").is_err());
+        assert!(parse_updates("many;3").is_err());
+        assert!(parse_updates("Some packages can be updated.").is_err());
+    }
+
+    #[test]
+    fn shows_reboot_badge_when_required() {
+        let root = tempfile_dir("reboot");
+        fs::write(root.join("updates-available"), UPDATES_AVAILABLE).unwrap();
+        let mut updates =
+            Updates::new(root.join("updates-available"), root.join("reboot-required"));
+
+        updates.update().unwrap();
+        assert_eq!(updates.updates, Some(12));
+        assert!(!updates.reboot_required);
+        assert_eq!(updates.height(10), 10);
+
+        fs::write(
+            root.join("reboot-required"),
+            "*** System restart required ***\n",
+        )
+        .unwrap();
+        updates.update().unwrap();
+        assert!(updates.reboot_required);
+        assert_eq!(updates.height(10), 20);
+    }
+
+    #[test]
+    fn shows_reboot_badge_without_update_count() {
+        let root = tempfile_dir("missing");
+        fs::write(root.join("reboot-required"), "").unwrap();
+        let mut updates = Updates::new(root.join("apt-check"), root.join("reboot-required"));
+
+        assert!(updates.update().is_err());
+        assert_eq!(updates.updates, None);
+        assert!(updates.reboot_required);
+    }
+}
@@ -0,0 +1,381 @@
+use super::bar::draw_bar;
+use super::text::draw_text;
+use super::{Component, Drawer, Error};
+
+use embedded_graphics::{prelude::*, text::Alignment};
+
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Write},
+    net::{TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+const DEFAULT_PORT: u16 = 3493;
+
+/// A UPS as given on the command line, `UPS@HOST[:PORT]` like in NUT's own
+/// tools
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UpsSpec {
+    pub name: String,
+    pub address: String,
+}
+
+impl std::str::FromStr for UpsSpec {
+    type Err = Error;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let Some((name, host)) = spec.split_once('@') else {
+            return Err(format!("'{}' is not in the format UPS@HOST[:PORT]", spec).into());
+        };
+        if name.is_empty() || host.is_empty() {
+            return Err(format!("'{}' is not in the format UPS@HOST[:PORT]", spec).into());
+        }
+
+        let address = match host.rsplit_once(':') {
+            Some((_, port)) if !host.ends_with(']') => {
+                port.parse::<u16>()
+                    .map_err(|_| format!("{} is not a valid port", port))?;
+                host.to_string()
+            }
+            _ => format!("{}:{}", host, DEFAULT_PORT),
+        };
+
+        Ok(Self {
+            name: name.to_string(),
+            address,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct UpsState {
+    // flags like OL for on line, OB for on battery and LB for low battery
+    status: Vec<String>,
+    charge: Option<f32>,
+    runtime: Option<Duration>,
+}
+
+impl UpsState {
+    fn flagged(&self, flag: &str) -> bool {
+        self.status.iter().any(|f| f == flag)
+    }
+
+    fn on_battery(&self) -> bool {
+        self.flagged("OB") || self.flagged("LB")
+    }
+
+    fn description(&self) -> &str {
+        if self.flagged("LB") {
+            "Low bat"
+        } else if self.flagged("OB") {
+            "Battery"
+        } else if self.flagged("OL") {
+            "Online"
+        } else {
+            self.status.first().map_or("?", String::as_str)
+        }
+    }
+}
+
+/// Power, charge and remaining runtime of a UPS, from a NUT `upsd` over its
+/// plain-text network protocol. Running on battery makes the component
+/// alert.
+#[derive(Clone, Debug)]
+pub struct Ups {
+    spec: UpsSpec,
+    timeout: Duration,
+    state: Option<UpsState>,
+}
+
+impl Ups {
+    pub fn new(spec: UpsSpec, timeout: Duration) -> Self {
+        Self {
+            spec,
+            timeout,
+            state: None,
+        }
+    }
+
+    /// All variables of the UPS, from `LIST VAR`
+    fn query(&self) -> Result<HashMap<String, String>, Error> {
+        let address = self
+            .spec
+            .address
+            .to_socket_addrs()
+            .map_err(|e| format!("Could not resolve {}: {}", self.spec.address, e))?
+            .next()
+            .ok_or_else(|| format!("{} has no address", self.spec.address))?;
+        let mut stream = TcpStream::connect_timeout(&address, self.timeout)
+            .map_err(|e| format!("Could not connect to upsd at {}: {}", address, e))?;
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+
+        stream.write_all(format!("LIST VAR {}\n", self.spec.name).as_bytes())?;
+        let mut response = String::new();
+        for line in BufReader::new(&stream).lines() {
+            let line = line?;
+            let done = line.starts_with("END LIST VAR") || line.starts_with("ERR ");
+            response.push_str(&line);
+            response.push('\n');
+            if done {
+                break;
+            }
+        }
+        // upsd closes the connection after this, there is no answer to wait for
+        let _ = stream.write_all(b"LOGOUT\n");
+
+        parse_variables(&response)
+    }
+}
+
+impl std::fmt::Display for Ups {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "UPS {}", self.spec.name)
+    }
+}
+
+impl Component for Ups {
+    fn update_interval(&self, configured: Option<Duration>) -> Option<Duration> {
+        Some(configured.unwrap_or(Duration::from_secs(5)))
+    }
+
+    fn update(&mut self) -> Result<(), Error> {
+        let result = self.query().and_then(|variables| parse_state(&variables));
+        self.state = result.as_ref().ok().cloned();
+        result.map(|_| ())
+    }
+
+    fn height(&self, line_height: u32) -> u32 {
+        2 * line_height
+    }
+
+    fn alerting(&self) -> bool {
+        self.state.as_ref().is_some_and(UpsState::on_battery)
+    }
+
+    fn draw(&self, drawable: &mut Drawer, offset: Point, tick: u64) -> Result<(), Error> {
+        let font = drawable.base_text_style.font;
+        let character_width = (font.character_size.width + font.character_spacing) as i32;
+        let width = Drawer::WIDTH as u32 + 1;
+
+        let Some(state) = &self.state else {
+            return draw_text(drawable, "UPS !", offset, width, Alignment::Left, tick);
+        };
+
+        let runtime = state.runtime.map(format_runtime).unwrap_or_default();
+        draw_text(
+            drawable,
+            state.description(),
+            offset,
+            width,
+            Alignment::Left,
+            tick,
+        )?;
+        draw_text(drawable, &runtime, offset, width, Alignment::Right, tick)?;
+
+        let charge_offset = offset + Point::new(0, drawable.line_height() as i32);
+        let charge = match state.charge {
+            Some(charge) => format!("{:.0}%", charge),
+            None => "-".to_string(),
+        };
+        // the bar ends a character before the percentage
+        let bar_width = Drawer::WIDTH as i32 - (charge.len() as i32 + 1) * character_width;
+        draw_bar(
+            drawable,
+            charge_offset,
+            bar_width,
+            state.charge.unwrap_or(0.0) / 100.0,
+        )?;
+        draw_text(
+            drawable,
+            &charge,
+            charge_offset,
+            width,
+            Alignment::Right,
+            tick,
+        )
+    }
+}
+
+/// The variables in a `LIST VAR` response, lines like
+/// `VAR ups battery.charge "100"` between `BEGIN LIST VAR ups` and
+/// `END LIST VAR ups`
+fn parse_variables(response: &str) -> Result<HashMap<String, String>, Error> {
+    let mut lines = response.lines();
+    match lines.next() {
+        Some(line) if line.starts_with("BEGIN LIST VAR ") => {}
+        Some(line) if line.starts_with("ERR ") => {
+            return Err(format!("upsd answered {}", line).into())
+        }
+        _ => return Err("upsd did not list any variables".into()),
+    }
+
+    let mut variables = HashMap::new();
+    for line in lines {
+        if line.starts_with("END LIST VAR ") {
+            return Ok(variables);
+        }
+        let parsed = line
+            .strip_prefix("VAR ")
+            .and_then(|line| line.split_once(' '))
+            .and_then(|(_ups, line)| line.split_once(' '))
+            .map(|(name, value)| (name, value.trim_matches('"')));
+        let Some((name, value)) = parsed else {
+            return Err(format!("Invalid upsd variable '{}'", line).into());
+        };
+        variables.insert(name.to_string(), value.replace("\\\"", "\""));
+    }
+
+    Err("upsd did not finish the list of variables".into())
+}
+
+fn parse_state(variables: &HashMap<String, String>) -> Result<UpsState, Error> {
+    let status = variables
+        .get("ups.status")
+        .ok_or("UPS reports no ups.status")?
+        .split_whitespace()
+        .map(str::to_string)
+        .collect();
+
+    Ok(UpsState {
+        status,
+        charge: variables
+            .get("battery.charge")
+            .and_then(|charge| charge.parse().ok()),
+        runtime: variables
+            .get("battery.runtime")
+            .and_then(|runtime| runtime.parse::<f64>().ok())
+            .map(Duration::from_secs_f64),
+    })
+}
+
+/// `runtime` like 45m or 1h05
+fn format_runtime(runtime: Duration) -> String {
+    let minutes = runtime.as_secs() / 60;
+    match minutes {
+        m if m < 60 => format!("{}m", m),
+        m => format!("{}h{:02}", m / 60, m % 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    const ON_BATTERY: &str = "\
+BEGIN LIST VAR nas
+VAR nas battery.charge \"87\"
+VAR nas battery.runtime \"2710\"
+VAR nas device.mfr \"American Power Conversion\"
+VAR nas ups.status \"OB DISCHRG\"
+END LIST VAR nas
+";
+
+    /// Answers one connection like upsd would, returning its address
+    fn upsd(response: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+
+        std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(&stream);
+            let mut request = String::new();
+            reader.read_line(&mut request).unwrap();
+            let response = match request.as_str() {
+                "LIST VAR nas\n" => response,
+                _ => "ERR UNKNOWN-UPS\n",
+            };
+            (&stream).write_all(response.as_bytes()).unwrap();
+        });
+
+        address
+    }
+
+    #[test]
+    fn parses_specs() {
+        assert_eq!(
+            "nas@localhost".parse::<UpsSpec>().unwrap(),
+            UpsSpec {
+                name: "nas".to_string(),
+                address: "localhost:3493".to_string(),
+            }
+        );
+        assert_eq!(
+            "nas@192.168.1.5:3494".parse::<UpsSpec>().unwrap().address,
+            "192.168.1.5:3494"
+        );
+        assert_eq!(
+            "nas@[::1]".parse::<UpsSpec>().unwrap().address,
+            "[::1]:3493"
+        );
+        assert!("nas".parse::<UpsSpec>().is_err());
+        assert!("@localhost".parse::<UpsSpec>().is_err());
+        assert!("nas@localhost:nut".parse::<UpsSpec>().is_err());
+    }
+
+    #[test]
+    fn parses_variables() {
+        let variables = parse_variables(ON_BATTERY).unwrap();
+        assert_eq!(variables["device.mfr"], "American Power Conversion");
+
+        let state = parse_state(&variables).unwrap();
+        assert_eq!(state.charge, Some(87.0));
+        assert_eq!(state.runtime, Some(Duration::from_secs(2710)));
+        assert!(state.on_battery());
+        assert_eq!(state.description(), "Battery");
+    }
+
+    #[test]
+    fn rejects_errors_and_incomplete_lists() {
+        assert!(parse_variables("ERR ACCESS-DENIED\n").is_err());
+        assert!(parse_variables("BEGIN LIST VAR nas\nVAR nas ups.status \"OL\"\n").is_err());
+        assert!(parse_state(&HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn queries_upsd() {
+        let address = upsd(ON_BATTERY);
+        let spec = format!("nas@{}", address).parse().unwrap();
+        let mut ups = Ups::new(spec, Duration::from_secs(5));
+
+        ups.update().unwrap();
+        assert_eq!(
+            ups.state.as_ref().and_then(|state| state.charge),
+            Some(87.0)
+        );
+        assert!(ups.alerting());
+    }
+
+    #[test]
+    fn online_ups_does_not_alert() {
+        let address =
+            upsd("BEGIN LIST VAR nas\nVAR nas ups.status \"OL CHRG\"\nEND LIST VAR nas\n");
+        let spec = format!("nas@{}", address).parse().unwrap();
+        let mut ups = Ups::new(spec, Duration::from_secs(5));
+
+        ups.update().unwrap();
+        assert_eq!(
+            ups.state.as_ref().map(UpsState::description),
+            Some("Online")
+        );
+        assert!(!ups.alerting());
+    }
+
+    #[test]
+    fn unknown_ups_is_an_error() {
+        let address = upsd(ON_BATTERY);
+        let spec = format!("basement@{}", address).parse().unwrap();
+        let mut ups = Ups::new(spec, Duration::from_secs(5));
+
+        assert!(ups.update().is_err());
+        assert_eq!(ups.state, None);
+    }
+
+    #[test]
+    fn formats_runtimes() {
+        assert_eq!(format_runtime(Duration::from_secs(2710)), "45m");
+        assert_eq!(format_runtime(Duration::from_secs(3900)), "1h05");
+    }
+}
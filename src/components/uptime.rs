@@ -1,9 +1,7 @@
+use super::text::draw_text;
 use super::{Component, Drawer, Error};
 
-use embedded_graphics::{
-    prelude::*,
-    text::{Baseline, Text},
-};
+use embedded_graphics::{prelude::*, text::Alignment};
 
 use systemstat::{Platform, System};
 
@@ -21,6 +19,15 @@ impl Uptime {
     }
 }
 
+impl Clone for Uptime {
+    fn clone(&self) -> Self {
+        Self {
+            sys: System::new(), // stateless
+            display_string: self.display_string.clone(),
+        }
+    }
+}
+
 impl std::fmt::Display for Uptime {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "Uptime")
@@ -34,8 +41,11 @@ impl std::fmt::Debug for Uptime {
 }
 
 impl Component for Uptime {
-    fn should_update(&self, last_update: std::time::Duration) -> bool {
-        last_update > std::time::Duration::from_secs(15)
+    fn update_interval(
+        &self,
+        configured: Option<std::time::Duration>,
+    ) -> Option<std::time::Duration> {
+        Some(configured.unwrap_or(std::time::Duration::from_secs(15)))
     }
 
     fn update(&mut self) -> Result<(), Error> {
@@ -49,15 +59,14 @@ impl Component for Uptime {
         Ok(())
     }
 
-    fn draw(&self, drawable: &mut Drawer, offset: Point, _tick: u64) -> Result<(), Error> {
-        Text::with_baseline(
+    fn draw(&self, drawable: &mut Drawer, offset: Point, tick: u64) -> Result<(), Error> {
+        draw_text(
+            drawable,
             &self.display_string,
             offset,
-            drawable.base_text_style,
-            Baseline::Top,
+            Drawer::WIDTH as u32 + 1,
+            Alignment::Left,
+            tick,
         )
-        .draw(&mut drawable.display)?;
-
-        Ok(())
     }
 }
@@ -0,0 +1,321 @@
+use super::text::draw_text;
+use super::{Component, Drawer, Error};
+
+use embedded_graphics::{prelude::*, text::Alignment};
+
+use std::{
+    fs,
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+// characters of a peer's public key shown to tell peers apart
+const KEY_CHARACTERS: usize = 4;
+
+/// A WireGuard interface as given on the command line, `INTERFACE` or
+/// `INTERFACE:PATH` to read the `wg show INTERFACE dump` output from a file
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WireGuardSpec {
+    pub interface: String,
+    pub dump: Option<PathBuf>,
+}
+
+impl std::str::FromStr for WireGuardSpec {
+    type Err = Error;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let (interface, dump) = match spec.split_once(':') {
+            Some((interface, dump)) => (interface, Some(PathBuf::from(dump))),
+            None => (spec, None),
+        };
+
+        if interface.is_empty() {
+            return Err("WireGuard interface must not be empty".into());
+        }
+
+        Ok(Self {
+            interface: interface.to_string(),
+            dump,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Peer {
+    public_key: String,
+    // seconds since the epoch, `None` if there never was a handshake
+    latest_handshake: Option<u64>,
+}
+
+impl Peer {
+    /// Seconds since the latest handshake at `now`
+    fn handshake_age(&self, now: SystemTime) -> Option<u64> {
+        let now = now
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or(Duration::ZERO)
+            .as_secs();
+        self.latest_handshake
+            .map(|handshake| now.saturating_sub(handshake))
+    }
+}
+
+/// The peers of a WireGuard interface and how long ago each one last
+/// completed a handshake. Peers without a recent handshake are flagged.
+#[derive(Clone, Debug)]
+pub struct WireGuard {
+    spec: WireGuardSpec,
+    max_handshake_age: Duration,
+    peers: Option<Vec<Peer>>,
+}
+
+impl WireGuard {
+    pub fn new(spec: WireGuardSpec, max_handshake_age: Duration) -> Self {
+        Self {
+            spec,
+            max_handshake_age,
+            peers: None,
+        }
+    }
+
+    fn read_dump(&self) -> Result<String, Error> {
+        if let Some(path) = &self.spec.dump {
+            return fs::read_to_string(path)
+                .map_err(|e| format!("Could not read {}: {}", path.display(), e).into());
+        }
+
+        let output = std::process::Command::new("wg")
+            .args(["show", &self.spec.interface, "dump"])
+            .stdin(std::process::Stdio::null())
+            .output()
+            .map_err(|e| format!("Could not run wg: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "wg show {} dump failed: {}",
+                self.spec.interface,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )
+            .into());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    fn stale(&self, peer: &Peer, now: SystemTime) -> bool {
+        peer.handshake_age(now)
+            .is_none_or(|age| age > self.max_handshake_age.as_secs())
+    }
+}
+
+impl std::fmt::Display for WireGuard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "WireGuard {}", self.spec.interface)
+    }
+}
+
+impl Component for WireGuard {
+    fn update_interval(&self, configured: Option<Duration>) -> Option<Duration> {
+        Some(configured.unwrap_or(Duration::from_secs(10)))
+    }
+
+    fn update(&mut self) -> Result<(), Error> {
+        let result = self.read_dump().and_then(|dump| parse_dump(&dump));
+        self.peers = result.as_ref().ok().cloned();
+        result.map(|_| ())
+    }
+
+    fn height(&self, line_height: u32) -> u32 {
+        let peers = self.peers.as_ref().map_or(0, Vec::len) as u32;
+        line_height * (1 + peers)
+    }
+
+    fn alerting(&self) -> bool {
+        let now = SystemTime::now();
+        self.peers
+            .iter()
+            .flatten()
+            .any(|peer| self.stale(peer, now))
+    }
+
+    fn draw(&self, drawable: &mut Drawer, offset: Point, tick: u64) -> Result<(), Error> {
+        let width = Drawer::WIDTH as u32 + 1;
+        let line_height = drawable.line_height() as i32;
+        let now = SystemTime::now();
+
+        draw_text(
+            drawable,
+            &self.spec.interface,
+            offset,
+            width,
+            Alignment::Left,
+            tick,
+        )?;
+        let count = match &self.peers {
+            Some(peers) => peers.len().to_string(),
+            None => "!".to_string(),
+        };
+        draw_text(drawable, &count, offset, width, Alignment::Right, tick)?;
+
+        for (i, peer) in self.peers.iter().flatten().enumerate() {
+            let row = offset + Point::new(0, line_height * (i as i32 + 1));
+            let key: String = peer.public_key.chars().take(KEY_CHARACTERS).collect();
+            let age = match peer.handshake_age(now) {
+                Some(age) => format_age(age),
+                None => "-".to_string(),
+            };
+            let age = match self.stale(peer, now) {
+                true => format!("!{}", age),
+                false => age,
+            };
+
+            draw_text(drawable, &key, row, width, Alignment::Left, tick)?;
+            draw_text(drawable, &age, row, width, Alignment::Right, tick)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The peers in the output of `wg show INTERFACE dump`. The first line
+/// describes the interface, every further line a peer with its public key,
+/// preshared key, endpoint, allowed IPs, latest handshake, received and sent
+/// bytes and keepalive interval, separated by tabs.
+fn parse_dump(dump: &str) -> Result<Vec<Peer>, Error> {
+    dump.lines()
+        .skip(1)
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let fields: Vec<&str> = line.split('\t').collect();
+            let [public_key, _, _, _, handshake, _, _, _] = fields[..] else {
+                return Err(format!("Invalid WireGuard peer '{}'", line).into());
+            };
+            let handshake: u64 = handshake
+                .parse()
+                .map_err(|_| format!("Invalid WireGuard handshake time '{}'", handshake))?;
+
+            Ok(Peer {
+                public_key: public_key.to_string(),
+                latest_handshake: Some(handshake).filter(|handshake| *handshake > 0),
+            })
+        })
+        .collect()
+}
+
+/// `seconds` in the largest unit that fits, like 45s, 12m, 3h or 4d
+fn format_age(seconds: u64) -> String {
+    match seconds {
+        s if s < 60 => format!("{}s", s),
+        s if s < 60 * 60 => format!("{}m", s / 60),
+        s if s < 24 * 60 * 60 => format!("{}h", s / (60 * 60)),
+        s => format!("{}d", s / (24 * 60 * 60)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DUMP: &str = "\
+yAnz5TF+lXXJte14tji3zlMNq+hd2rYUIgJBgB3fBmk=\tHIgo9xNzJMWLKASShiTqIybxZ0U3wGLiUeJ1PKf8ykw=\t51820\toff
+xTIBA5rboUvnH4htodjb6e697QjLERt1NAB4mZqp8Dg=\t(none)\t192.0.2.10:51820\t10.0.0.2/32\t1000\t5000\t6000\t25
+TrMvSoP4jYQlY6RIzBgbssQqY3vxI2Pi+y71lOWWXX0=\t(none)\t(none)\t10.0.0.3/32\t0\t0\t0\toff
+gN65BkIKy1eCE9pP1wdc8ROUtkHLF2PfAqYdyYBz6EA=\t(none)\t198.51.100.7:51820\t10.0.0.4/32\t500\t10\t20\toff
+";
+
+    fn at(seconds: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(seconds)
+    }
+
+    fn tempfile_dir(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!(
+            "oled-wireguard-test-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir(&root).unwrap();
+        root
+    }
+
+    #[test]
+    fn parses_specs() {
+        assert_eq!(
+            "wg0".parse::<WireGuardSpec>().unwrap(),
+            WireGuardSpec {
+                interface: "wg0".to_string(),
+                dump: None,
+            }
+        );
+        assert_eq!(
+            "wg0:/run/wg0.dump".parse::<WireGuardSpec>().unwrap(),
+            WireGuardSpec {
+                interface: "wg0".to_string(),
+                dump: Some(PathBuf::from("/run/wg0.dump")),
+            }
+        );
+        assert!(":/run/wg0.dump".parse::<WireGuardSpec>().is_err());
+    }
+
+    #[test]
+    fn parses_peers_from_dump() {
+        let peers = parse_dump(DUMP).unwrap();
+
+        assert_eq!(peers.len(), 3);
+        assert_eq!(
+            peers[0],
+            Peer {
+                public_key: "xTIBA5rboUvnH4htodjb6e697QjLERt1NAB4mZqp8Dg=".to_string(),
+                latest_handshake: Some(1000),
+            }
+        );
+        assert_eq!(peers[1].latest_handshake, None);
+    }
+
+    #[test]
+    fn rejects_invalid_dumps() {
+        assert!(parse_dump("interface\npeer\t(none)\n").is_err());
+        assert!(parse_dump("interface\npeer\t(none)\t(none)\t(none)\tsoon\t0\t0\toff\n").is_err());
+        assert_eq!(parse_dump("interface\n").unwrap(), vec![]);
+    }
+
+    #[test]
+    fn flags_peers_without_recent_handshake() {
+        let mut wireguard = WireGuard::new("wg0".parse().unwrap(), Duration::from_secs(180));
+        wireguard.peers = Some(parse_dump(DUMP).unwrap());
+        let peers = wireguard.peers.clone().unwrap();
+
+        let stale: Vec<bool> = peers.iter().map(|p| wireguard.stale(p, at(1100))).collect();
+        // recent, never and 10 minutes ago
+        assert_eq!(stale, vec![false, true, true]);
+        assert_eq!(peers[0].handshake_age(at(1100)), Some(100));
+    }
+
+    #[test]
+    fn reads_dump_from_file() {
+        let root = tempfile_dir("dump");
+        let path = root.join("wg0.dump");
+        fs::write(&path, DUMP).unwrap();
+        let spec = WireGuardSpec {
+            interface: "wg0".to_string(),
+            dump: Some(path),
+        };
+        let mut wireguard = WireGuard::new(spec, Duration::from_secs(180));
+
+        wireguard.update().unwrap();
+        assert_eq!(wireguard.peers.as_ref().map(Vec::len), Some(3));
+        assert_eq!(wireguard.height(10), 40);
+        // the fixture's handshakes are decades old
+        assert!(wireguard.alerting());
+
+        fs::remove_file(root.join("wg0.dump")).unwrap();
+        assert!(wireguard.update().is_err());
+        assert_eq!(wireguard.peers, None);
+        assert_eq!(wireguard.height(10), 10);
+    }
+
+    #[test]
+    fn formats_ages() {
+        assert_eq!(format_age(45), "45s");
+        assert_eq!(format_age(12 * 60 + 5), "12m");
+        assert_eq!(format_age(3 * 60 * 60), "3h");
+        assert_eq!(format_age(4 * 24 * 60 * 60), "4d");
+    }
+}
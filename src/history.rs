@@ -0,0 +1,231 @@
+use crate::components::Component;
+use crate::Error;
+
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How often histories are written to the state directory
+pub const SAVE_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Timestamped samples of a graph, newest in front. The graph's columns are
+/// derived from the time of each sample, so periods without samples, like
+/// while the service was stopped, show up as gaps.
+#[derive(Clone, Debug, PartialEq)]
+pub struct History {
+    capacity: usize,
+    // seconds since the epoch and value
+    samples: VecDeque<(u64, f32)>,
+    // the usual number of seconds between samples, once known
+    step: Option<u64>,
+}
+
+impl History {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            samples: VecDeque::with_capacity(capacity),
+            step: None,
+        }
+    }
+
+    pub fn push(&mut self, time: SystemTime, value: f32) {
+        if self.samples.len() == self.capacity {
+            // at capacity, remove oldest data point
+            self.samples.pop_back();
+        }
+        self.samples.push_front((unix_seconds(time), value));
+        self.step = self.median_spacing().or(self.step);
+    }
+
+    /// The samples with the column they fall into at `now`, 0 being the
+    /// newest column. Samples that are too old for the graph are left out.
+    pub fn columns(&self, now: SystemTime) -> impl Iterator<Item = (usize, f32)> + '_ {
+        let now = unix_seconds(now);
+        let step = self.step;
+
+        self.samples
+            .iter()
+            .enumerate()
+            .map(move |(i, (time, value))| {
+                let column = match step {
+                    Some(step) => (now.saturating_sub(*time) / step) as usize,
+                    None => i,
+                };
+                (column, *value)
+            })
+            .filter(|(column, _)| *column < self.capacity)
+    }
+
+    /// The usual number of seconds between samples, once known
+    pub fn step(&self) -> Option<u64> {
+        self.step
+    }
+
+    /// The median of the spacing between consecutive samples, so gaps don't
+    /// skew it
+    fn median_spacing(&self) -> Option<u64> {
+        let mut spacings: Vec<u64> = self
+            .samples
+            .iter()
+            .zip(self.samples.iter().skip(1))
+            .map(|((newer, _), (older, _))| newer.saturating_sub(*older))
+            .collect();
+        spacings.sort_unstable();
+
+        spacings
+            .get(spacings.len() / 2)
+            .copied()
+            .filter(|step| *step > 0)
+    }
+
+    /// Replaces the samples with those saved at `path`, dropping the ones
+    /// that are too old to be shown at `now`. A missing file is no error,
+    /// there just is no history yet.
+    pub fn load(&mut self, path: &Path, now: SystemTime) -> Result<(), Error> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+
+        self.samples.clear();
+        for line in content.lines() {
+            let parsed = line
+                .split_once(' ')
+                .and_then(|(time, value)| Some((time.parse().ok()?, value.parse().ok()?)));
+            let Some((time, value)) = parsed else {
+                return Err(format!("Invalid history entry '{}'", line).into());
+            };
+            // the file is oldest first
+            self.push(UNIX_EPOCH + Duration::from_secs(time), value);
+        }
+
+        let shown = self.columns(now).count();
+        self.samples.truncate(shown);
+        Ok(())
+    }
+
+    /// Writes the samples to `path`, oldest first. The file is replaced
+    /// atomically so a crash never leaves a truncated history.
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        let content: String = self
+            .samples
+            .iter()
+            .rev()
+            .map(|(time, value)| format!("{} {}\n", time, value))
+            .collect();
+
+        let temporary = path.with_extension("tmp");
+        fs::write(&temporary, content)?;
+        fs::rename(&temporary, path)?;
+        Ok(())
+    }
+}
+
+/// Where the history of `component` is kept in `state_dir`
+pub fn state_file(state_dir: &Path, component: &dyn Component) -> PathBuf {
+    let name: String = component
+        .to_string()
+        .chars()
+        .map(|c| match c.is_ascii_alphanumeric() {
+            true => c.to_ascii_lowercase(),
+            false => '-',
+        })
+        .collect();
+    state_dir.join(format!("{}.history", name))
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(seconds: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(seconds)
+    }
+
+    fn tempfile_dir(name: &str) -> PathBuf {
+        let root =
+            std::env::temp_dir().join(format!("oled-history-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir(&root).unwrap();
+        root
+    }
+
+    #[test]
+    fn keeps_capacity() {
+        let mut history = History::new(2);
+        for i in 0..3 {
+            history.push(at(1000 + i * 60), i as f32);
+        }
+
+        assert_eq!(
+            history.columns(at(1120)).collect::<Vec<_>>(),
+            vec![(0, 2.0), (1, 1.0)]
+        );
+    }
+
+    #[test]
+    fn missing_samples_leave_gaps() {
+        let mut history = History::new(60);
+        for time in [1000, 1060, 1120, 1420, 1480] {
+            history.push(at(time), 0.5);
+        }
+
+        let columns: Vec<usize> = history.columns(at(1490)).map(|(c, _)| c).collect();
+        assert_eq!(columns, vec![0, 1, 6, 7, 8]);
+    }
+
+    #[test]
+    fn round_trips_through_state_file() {
+        let root = tempfile_dir("round-trip");
+        let path = root.join("load.history");
+        let mut history = History::new(60);
+        history.push(at(1000), 0.25);
+        history.push(at(1060), 0.75);
+        history.save(&path).unwrap();
+
+        let mut loaded = History::new(60);
+        loaded.load(&path, at(1080)).unwrap();
+        assert_eq!(loaded, history);
+    }
+
+    #[test]
+    fn drops_samples_older_than_the_graph() {
+        let root = tempfile_dir("old");
+        let path = root.join("load.history");
+        fs::write(&path, "1000 0.1\n1060 0.2\n1120 0.3\n").unwrap();
+
+        let mut history = History::new(3);
+        history.load(&path, at(1250)).unwrap();
+        assert_eq!(
+            history.columns(at(1250)).collect::<Vec<_>>(),
+            vec![(2, 0.3)]
+        );
+    }
+
+    #[test]
+    fn missing_state_file_is_empty_history() {
+        let root = tempfile_dir("missing");
+        let mut history = History::new(3);
+
+        history.load(&root.join("load.history"), at(0)).unwrap();
+        assert_eq!(history.columns(at(0)).count(), 0);
+    }
+
+    #[test]
+    fn names_state_files_after_components() {
+        let hostname = crate::components::Hostname { hostname: None };
+        assert_eq!(
+            state_file(Path::new("/var/lib/oled"), &hostname),
+            PathBuf::from("/var/lib/oled/hostname.history")
+        );
+    }
+}
@@ -0,0 +1,139 @@
+use crate::Error;
+
+use std::io::{Read, Write};
+#[cfg(feature = "kubernetes")]
+use std::net::{TcpStream, ToSocketAddrs};
+#[cfg(feature = "kubernetes")]
+use std::time::Duration;
+
+/// Fetches `url`, which must be a plain `http://` URL, returning the body
+/// of a successful response
+#[cfg(feature = "kubernetes")]
+pub fn get_url(url: &str, timeout: Duration) -> Result<String, Error> {
+    let rest = url
+        .strip_prefix("http://")
+        .ok_or_else(|| format!("{} is not an http:// URL", url))?;
+    let (authority, path) = match rest.find('/') {
+        Some(slash) => rest.split_at(slash),
+        None => (rest, "/"),
+    };
+    let address = match authority.contains(':') && !authority.ends_with(']') {
+        true => authority.to_string(),
+        false => format!("{}:80", authority),
+    };
+    let address = address
+        .to_socket_addrs()
+        .map_err(|e| format!("Could not resolve {}: {}", authority, e))?
+        .next()
+        .ok_or_else(|| format!("{} has no address", authority))?;
+
+    let stream = TcpStream::connect_timeout(&address, timeout)
+        .map_err(|e| format!("Could not connect to {}: {}", authority, e))?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+    get(stream, authority, path)
+}
+
+/// Sends a GET request for `path` over `stream` and returns the body of a
+/// successful response. HTTP/1.0 keeps servers from chunking the response
+/// and makes them close the connection when they are done.
+pub fn get(mut stream: impl Read + Write, host: &str, path: &str) -> Result<String, Error> {
+    let request = format!(
+        "GET {} HTTP/1.0\r\nHost: {}\r\nAccept: application/json\r\n\r\n",
+        path, host
+    );
+    stream.write_all(request.as_bytes())?;
+    stream.flush()?;
+
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response)?;
+    parse_response(&String::from_utf8_lossy(&response))
+}
+
+fn parse_response(response: &str) -> Result<String, Error> {
+    let (head, body) = response
+        .split_once("\r\n\r\n")
+        .ok_or("Incomplete HTTP response")?;
+    let mut lines = head.lines();
+    let status_line = lines.next().unwrap_or("");
+    let status = status_line
+        .split(' ')
+        .nth(1)
+        .and_then(|status| status.parse::<u16>().ok())
+        .ok_or_else(|| format!("Invalid HTTP status line '{}'", status_line))?;
+
+    if !(200..300).contains(&status) {
+        return Err(format!("HTTP request failed: {}", status_line).into());
+    }
+    let chunked = lines.any(|line| {
+        line.to_ascii_lowercase()
+            .starts_with("transfer-encoding: chunked")
+    });
+    if chunked {
+        return Err("Chunked HTTP responses are not supported".into());
+    }
+
+    Ok(body.to_string())
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    /// Reads a request up to the end of its headers
+    pub fn read_request(stream: &mut impl Read) -> String {
+        let mut request = Vec::new();
+        let mut byte = [0u8];
+        while !request.ends_with(b"\r\n\r\n") && stream.read(&mut byte).unwrap() == 1 {
+            request.push(byte[0]);
+        }
+        String::from_utf8_lossy(&request).into_owned()
+    }
+
+    #[test]
+    fn parses_successful_responses() {
+        assert_eq!(
+            parse_response("HTTP/1.0 200 OK\r\nContent-Type: text/plain\r\n\r\nok").unwrap(),
+            "ok"
+        );
+        assert_eq!(
+            parse_response("HTTP/1.1 204 No Content\r\n\r\n").unwrap(),
+            ""
+        );
+    }
+
+    #[test]
+    fn rejects_failed_and_invalid_responses() {
+        assert!(parse_response("HTTP/1.0 500 Internal Server Error\r\n\r\noops").is_err());
+        assert!(parse_response("HTTP/1.0 200 OK\r\nContent-Type").is_err());
+        assert!(parse_response("SSH-2.0-OpenSSH\r\n\r\n").is_err());
+        assert!(
+            parse_response("HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n2\r\nok").is_err()
+        );
+    }
+
+    #[cfg(feature = "kubernetes")]
+    #[test]
+    fn fetches_from_local_server() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/healthz", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let request = read_request(&mut stream);
+            stream.write_all(b"HTTP/1.0 200 OK\r\n\r\nok").unwrap();
+            request
+        });
+
+        assert_eq!(get_url(&url, Duration::from_secs(5)).unwrap(), "ok");
+        assert!(server
+            .join()
+            .unwrap()
+            .starts_with("GET /healthz HTTP/1.0\r\n"));
+    }
+
+    #[cfg(feature = "kubernetes")]
+    #[test]
+    fn rejects_other_schemes() {
+        assert!(get_url("https://127.0.0.1/", Duration::from_secs(1)).is_err());
+    }
+}
@@ -10,7 +10,8 @@ use embedded_graphics::{
     mono_font::{ascii::FONT_6X10, MonoTextStyle, MonoTextStyleBuilder},
     pixelcolor::BinaryColor,
     prelude::*,
-    primitives::PrimitiveStyle,
+    primitives::{PrimitiveStyle, Rectangle},
+    text::{Baseline, Text},
 };
 #[cfg(feature = "simulator")]
 use embedded_graphics_simulator::{
@@ -30,20 +31,43 @@ use std::fs::File;
 use std::io::Write;
 use std::path::{Path, PathBuf};
 
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime};
 
 use clap::Parser;
 
 mod components;
 use components::{
-    Component, Disk, Hostname, Load, Memory, NetworkThroughput, UpdateIndicator, Uptime,
+    Address, Clock, Command, CommandSpec, Component, Containers, Disk, GraphMode, Hostname, Load,
+    Memory, NetworkGraph, NetworkOptions, NetworkSource, NetworkThroughput, PeakWindow, Pressure,
+    Probe, ProbeTarget, QrCode, StatusFile, StatusFileSpec, Systemd, UpdateIndicator, Updates, Ups,
+    UpsSpec, Uptime, WireGuard, WireGuardSpec,
 };
 
+mod collector;
+use collector::Collector;
+
+mod history;
+pub use history::History;
+
+mod alert;
+pub use alert::Threshold;
+
+mod settings;
+pub use settings::{ComponentSetting, Font};
+
 mod units;
 pub use units::{Base, GlancableSizesWithOrdersOfMagnitude};
 
+mod scheduler;
+use scheduler::Scheduler;
+
 mod signals;
 
+mod notify;
+use notify::Notifier;
+
+mod http;
+
 #[cfg(feature = "i2c")]
 type Display = Ssd1306<
     I2CInterface<EmbeddedHALWriter<File>>,
@@ -147,6 +171,7 @@ impl embedded_hal::i2c::Error for IoError {
     }
 }
 #[cfg(feature = "i2c")]
+#[allow(clippy::from_over_into)]
 impl Into<std::io::Error> for IoError {
     fn into(self) -> std::io::Error {
         self.error
@@ -229,13 +254,19 @@ pub struct Drawer<'a> {
     window: Window,
     base_text_style: MonoTextStyle<'a, BinaryColor>,
     base_primitive_style: PrimitiveStyle<BinaryColor>,
+    font: Font,
+    component_fonts: Vec<ComponentSetting<Font>>,
+    graph_modes: Vec<ComponentSetting<GraphMode>>,
+    graph_threshold_settings: Vec<ComponentSetting<f32>>,
+    // graph settings of the component being drawn
+    graph_mode: Option<GraphMode>,
+    graph_thresholds: Vec<f32>,
 }
 
 impl Drawer<'_> {
     pub const BURNIN_OFFSET_MAX: u8 = 5;
     pub const WIDTH: u8 = 64 - Self::BURNIN_OFFSET_MAX;
     pub const HEIGHT: u8 = 128 - Self::BURNIN_OFFSET_MAX;
-    pub const LINE_HEIGHT: u8 = 11;
 
     #[cfg(feature = "i2c")]
     pub fn new_from_device_path(path: &Path, brightness: Brightness) -> Result<Self, Error> {
@@ -256,6 +287,12 @@ impl Drawer<'_> {
                 .text_color(BinaryColor::On)
                 .build(),
             base_primitive_style: PrimitiveStyle::with_stroke(BinaryColor::On, 1),
+            font: Font(&FONT_6X10),
+            component_fonts: Vec::new(),
+            graph_modes: Vec::new(),
+            graph_threshold_settings: Vec::new(),
+            graph_mode: None,
+            graph_thresholds: Vec::new(),
         })
     }
 
@@ -274,10 +311,44 @@ impl Drawer<'_> {
                 .text_color(BinaryColor::On)
                 .build(),
             base_primitive_style: PrimitiveStyle::with_stroke(BinaryColor::On, 1),
+            font: Font(&FONT_6X10),
+            component_fonts: Vec::new(),
+            graph_modes: Vec::new(),
+            graph_threshold_settings: Vec::new(),
+            graph_mode: None,
+            graph_thresholds: Vec::new(),
         })
     }
 
-    pub fn draw(&mut self, tick: u64, components: &mut [Box<dyn Component>]) -> Result<(), Error> {
+    /// Use `font` for all components but those with a font in `component_fonts`.
+    pub fn set_fonts(&mut self, font: Font, component_fonts: Vec<ComponentSetting<Font>>) {
+        self.font = font;
+        self.component_fonts = component_fonts;
+    }
+
+    /// Override how graphs of the named components are drawn and add
+    /// threshold lines to them.
+    pub fn set_graph_styles(
+        &mut self,
+        graph_modes: Vec<ComponentSetting<GraphMode>>,
+        graph_thresholds: Vec<ComponentSetting<f32>>,
+    ) {
+        self.graph_modes = graph_modes;
+        self.graph_threshold_settings = graph_thresholds;
+    }
+
+    /// The height of a line of text in the current component's font.
+    pub fn line_height(&self) -> u32 {
+        self.base_text_style.font.character_size.height + 1
+    }
+
+    /// Draws the components from top to bottom. Stale components, whose
+    /// latest update didn't finish in time, are underlined with a dotted line.
+    pub fn draw<'c>(
+        &mut self,
+        tick: u64,
+        components: impl Iterator<Item = (&'c dyn Component, bool)>,
+    ) -> Result<(), Error> {
         let burn_in_offset = Point::new(
             (tick / 17u64 % Self::BURNIN_OFFSET_MAX as u64) as i32,
             (tick / 11u64 % Self::BURNIN_OFFSET_MAX as u64) as i32,
@@ -285,12 +356,46 @@ impl Drawer<'_> {
 
         self.display.clear(BinaryColor::Off)?;
 
-        for (i, c) in components.iter().enumerate() {
-            c.draw(
-                self,
-                burn_in_offset + Point::new(0, (Self::LINE_HEIGHT * i as u8).into()),
-                tick,
-            )?;
+        let mut y = 0i32;
+        for (c, stale) in components {
+            let font = settings::lookup(&self.component_fonts, c)
+                .copied()
+                .unwrap_or(self.font);
+            self.base_text_style.font = font.0;
+            self.graph_mode = settings::lookup(&self.graph_modes, c).copied();
+            self.graph_thresholds = settings::matching(&self.graph_threshold_settings, c)
+                .copied()
+                .collect();
+            let height = c.height(self.line_height());
+            let offset = burn_in_offset + Point::new(0, y);
+
+            // blink alerting components by inverting them every other tick
+            if c.alerting() && tick.is_multiple_of(2) {
+                Rectangle::new(offset, Size::new(Self::WIDTH as u32 + 1, height))
+                    .into_styled(PrimitiveStyle::with_fill(BinaryColor::On))
+                    .draw(&mut self.display)?;
+
+                self.base_text_style.text_color = Some(BinaryColor::Off);
+                self.base_primitive_style.stroke_color = Some(BinaryColor::Off);
+                let result = self.draw_component(c, offset, tick);
+                self.base_text_style.text_color = Some(BinaryColor::On);
+                self.base_primitive_style.stroke_color = Some(BinaryColor::On);
+                result?;
+            } else {
+                self.draw_component(c, offset, tick)?;
+            }
+
+            if stale {
+                // the spacing below the last line of the component
+                let bottom = offset + Point::new(0, height as i32 - 1);
+                self.display.draw_iter(
+                    (0..=Self::WIDTH as i32)
+                        .step_by(2)
+                        .map(|x| Pixel(bottom + Point::new(x, 0), BinaryColor::On)),
+                )?;
+            }
+
+            y += height as i32;
         }
 
         self.display.flush()?;
@@ -298,6 +403,30 @@ impl Drawer<'_> {
         self.window.update(&self.display.display);
         Ok(())
     }
+
+    /// Draws `c`, or its name with an error marker if it can't be drawn so
+    /// one broken component doesn't take down the display
+    fn draw_component(&mut self, c: &dyn Component, offset: Point, tick: u64) -> Result<(), Error> {
+        let Err(e) = c.draw(self, offset, tick) else {
+            return Ok(());
+        };
+        println!("Could not draw {}: {}", c, e);
+
+        // clear what was drawn before the error
+        let foreground = self.base_text_style.text_color.unwrap_or(BinaryColor::On);
+        let height = c.height(self.line_height());
+        Rectangle::new(offset, Size::new(Self::WIDTH as u32 + 1, height))
+            .into_styled(PrimitiveStyle::with_fill(foreground.invert()))
+            .draw(&mut self.display)?;
+        Text::with_baseline(
+            &format!("{} !", c),
+            offset,
+            self.base_text_style,
+            Baseline::Top,
+        )
+        .draw(&mut self.display)?;
+        Ok(())
+    }
 }
 
 #[cfg(feature = "i2c")]
@@ -345,6 +474,22 @@ struct Args {
     #[clap(short, long, env = "OLED_HOSTNAME")]
     hostname: Option<String>,
 
+    /// Show the local time, formatted with strftime conversions like %H:%M
+    #[clap(long, env = "OLED_CLOCK")]
+    clock: Option<String>,
+
+    /// Time zone file used by the clock
+    #[clap(
+        long = "clock-timezone",
+        env = "OLED_CLOCK_TIMEZONE",
+        default_value = "/etc/localtime"
+    )]
+    clock_timezone: PathBuf,
+
+    /// Draw the clock with large digits
+    #[clap(long = "clock-large")]
+    clock_large: bool,
+
     /// Enable CPU load graph
     #[clap(short, long)]
     load: bool,
@@ -353,9 +498,201 @@ struct Args {
     #[clap(short, long)]
     memory: bool,
 
-    /// Enable network throughput display for a network adapter
-    #[clap(short, long, env = "OLED_NETWORK")]
-    network: Option<String>,
+    /// Show swap and zram usage below the memory graph
+    #[clap(long = "memory-swap")]
+    memory_swap: bool,
+
+    /// Where to find procfs
+    #[clap(long = "procfs", env = "OLED_PROCFS", default_value = "/proc")]
+    procfs: PathBuf,
+
+    /// Where to find block devices in sysfs
+    #[clap(
+        long = "block-sysfs",
+        env = "OLED_BLOCK_SYSFS",
+        default_value = "/sys/block"
+    )]
+    block_sysfs: PathBuf,
+
+    /// Enable CPU, IO and memory pressure stall display
+    #[clap(long)]
+    pressure: bool,
+
+    /// Alert when a pressure stall average stays above this percentage
+    #[clap(long = "pressure-alert", default_value = "25")]
+    pressure_alert: f32,
+
+    /// Enable network throughput display for a network adapter, all adapters
+    /// matching a glob like eth* or the sum of all physical adapters with
+    /// `physical`. Can be repeated.
+    #[clap(short, long, env = "OLED_NETWORK", value_delimiter = ',')]
+    network: Vec<String>,
+
+    /// Show all network adapters on the same rows, taking turns
+    #[clap(long = "network-rotate")]
+    network_rotate: bool,
+
+    /// Show network throughput in packets instead of bytes per second, with
+    /// "pps" on the line above the rates
+    #[clap(long = "network-packets")]
+    network_packets: bool,
+
+    /// Graph the history of the network throughput below it, on an auto or
+    /// log scale
+    #[clap(long = "network-graph", env = "OLED_NETWORK_GRAPH")]
+    network_graph: Option<NetworkGraph>,
+
+    /// Show the peak network throughput since start or in the last hour
+    #[clap(long = "network-peak", env = "OLED_NETWORK_PEAK")]
+    network_peak: Option<PeakWindow>,
+
+    /// Enable IP address and link state display for a network adapter
+    #[clap(short, long, env = "OLED_ADDRESS")]
+    address: Option<String>,
+
+    /// Also show global IPv6 addresses
+    #[clap(long = "address-ipv6")]
+    address_ipv6: bool,
+
+    /// Show a QR code of this URL, `{ip}` is replaced with the address of the
+    /// --qr-interface network adapter
+    #[clap(long, env = "OLED_QR", requires = "qr_interface")]
+    qr: Option<String>,
+
+    /// Network adapter whose address is used in the QR code
+    #[clap(long = "qr-interface", env = "OLED_QR_INTERFACE")]
+    qr_interface: Option<String>,
+
+    /// Width and height of the QR code in pixels
+    #[clap(long = "qr-size", default_value = "60")]
+    qr_size: u32,
+
+    /// Display the output of a shell command, given as MODE:INTERVAL:COMMAND.
+    /// MODE is text, number (graphed) or fraction (a bar), INTERVAL is in
+    /// seconds. Can be repeated.
+    #[clap(long = "command")]
+    commands: Vec<CommandSpec>,
+
+    /// Seconds after which commands are killed
+    #[clap(long = "command-timeout", default_value = "10")]
+    command_timeout: u64,
+
+    /// Display the first line of a status file, or with PATH:KEY the value of
+    /// a key in a JSON object. Can be repeated.
+    #[clap(long = "status-file")]
+    status_files: Vec<StatusFileSpec>,
+
+    /// Mark status files that weren't modified for this many seconds
+    #[clap(long = "status-file-max-age")]
+    status_file_max_age: Option<u64>,
+
+    /// Display the peers of a WireGuard interface and the time since their
+    /// latest handshake. With INTERFACE:PATH the `wg show INTERFACE dump`
+    /// output is read from a file. Can be repeated.
+    #[clap(long = "wireguard", env = "OLED_WIREGUARD", value_delimiter = ',')]
+    wireguard: Vec<WireGuardSpec>,
+
+    /// Flag WireGuard peers whose latest handshake is older than this many
+    /// seconds
+    #[clap(long = "wireguard-max-handshake-age", default_value = "180")]
+    wireguard_max_handshake_age: u64,
+
+    /// Check whether a target is reachable and show its round trip time,
+    /// given as HOST:PORT or tcp:HOST:PORT to connect to a port, or
+    /// icmp:HOST to ping where unprivileged ping sockets are permitted. Can
+    /// be repeated.
+    #[clap(long = "probe", env = "OLED_PROBE", value_delimiter = ',')]
+    probes: Vec<ProbeTarget>,
+
+    /// Seconds between reachability checks
+    #[clap(long = "probe-interval", default_value = "30")]
+    probe_interval: u64,
+
+    /// Seconds after which a target counts as unreachable
+    #[clap(long = "probe-timeout", default_value = "2")]
+    probe_timeout: u64,
+
+    /// Enable display of the number of failed systemd units
+    #[clap(long, env = "OLED_SYSTEMD")]
+    systemd: bool,
+
+    /// Show the state of a systemd unit, implies --systemd. Can be repeated.
+    #[clap(
+        long = "systemd-unit",
+        env = "OLED_SYSTEMD_UNIT",
+        value_delimiter = ','
+    )]
+    systemd_units: Vec<String>,
+
+    /// Enable display of the number of upgradable packages and whether a
+    /// reboot is required
+    #[clap(long, env = "OLED_UPDATES")]
+    updates: bool,
+
+    /// File with the number of upgradable packages, in the format of
+    /// apt-check (UPDATES;SECURITY_UPDATES), as a plain number, or as cached
+    /// by Ubuntu's update-notifier in /var/lib/update-notifier/updates-available.
+    /// On Debian an apt hook like this in /etc/apt/apt.conf.d/99oled writes it:
+    /// APT::Update::Post-Invoke-Success { "apt-get -s upgrade | grep -c ^Inst
+    /// > /var/lib/apt/periodic/upgradable || true"; };
+    #[clap(
+        long = "updates-file",
+        env = "OLED_UPDATES_FILE",
+        default_value = "/var/lib/apt/periodic/upgradable"
+    )]
+    updates_file: PathBuf,
+
+    /// File whose existence means a reboot is required
+    #[clap(
+        long = "reboot-required-file",
+        env = "OLED_REBOOT_REQUIRED_FILE",
+        default_value = "/var/run/reboot-required"
+    )]
+    reboot_required_file: PathBuf,
+
+    /// Show power, charge and runtime of a UPS managed by NUT, given as
+    /// UPS@HOST[:PORT]. Can be repeated.
+    #[clap(long = "ups", env = "OLED_UPS", value_delimiter = ',')]
+    ups: Vec<UpsSpec>,
+
+    /// Enable display of running, stopped and unhealthy containers
+    #[clap(long, env = "OLED_CONTAINERS")]
+    containers: bool,
+
+    /// Unix socket of a Docker compatible API, like the one of Docker or
+    /// Podman
+    #[clap(
+        long = "container-socket",
+        env = "OLED_CONTAINER_SOCKET",
+        default_value = "/var/run/docker.sock"
+    )]
+    container_socket: PathBuf,
+
+    /// Enable display of the node's Ready state, its number of pods and pods
+    /// in CrashLoopBackOff
+    #[cfg(feature = "kubernetes")]
+    #[clap(long, env = "OLED_KUBERNETES")]
+    kubernetes: bool,
+
+    /// Base URL of the kubelet's read-only API
+    #[cfg(feature = "kubernetes")]
+    #[clap(
+        long = "kubelet-url",
+        env = "OLED_KUBELET_URL",
+        default_value = "http://127.0.0.1:10255"
+    )]
+    kubelet_url: String,
+
+    /// Base URL of the Kubernetes API, without authentication like behind
+    /// `kubectl proxy`. The node is the one of --hostname, or else the one
+    /// the kubelet's pods run on.
+    #[cfg(feature = "kubernetes")]
+    #[clap(
+        long = "kubernetes-api-url",
+        env = "OLED_KUBERNETES_API_URL",
+        default_value = "http://127.0.0.1:8001"
+    )]
+    kubernetes_api_url: String,
 
     /// Where to find network adapters in sysfs
     #[clap(
@@ -365,6 +702,41 @@ struct Args {
     )]
     network_sysfs: PathBuf,
 
+    /// Font used by all components, one of 4x6, 5x7, 5x8, 6x9, 6x10, 6x12,
+    /// 7x13, 8x13, 9x15 or 10x20
+    #[clap(long, env = "OLED_FONT", default_value = "6x10")]
+    font: Font,
+
+    /// Font of a single component, given as NAME=FONT where NAME is the
+    /// component's name like Load or Network eth0. Can be repeated.
+    #[clap(long = "component-font")]
+    component_fonts: Vec<ComponentSetting<Font>>,
+
+    /// Seconds between updates of a single component, given as NAME=SECONDS
+    /// where NAME is the component's name like Load or Network eth0. Can be
+    /// repeated.
+    #[clap(long = "update-interval")]
+    update_intervals: Vec<ComponentSetting<u64>>,
+
+    /// Seconds after which a component whose update hasn't finished is
+    /// marked as stale and updated on a new thread
+    #[clap(long = "update-timeout", default_value = "10")]
+    update_timeout: u64,
+
+    /// Graph mode of a single component, given as NAME=MODE where MODE is
+    /// line, bars or filled. Can be repeated.
+    #[clap(long = "graph-mode")]
+    graph_modes: Vec<ComponentSetting<GraphMode>>,
+
+    /// Threshold line in the graph of a single component, given as
+    /// NAME=VALUE in the units of the graph. Can be repeated.
+    #[clap(long = "graph-threshold")]
+    graph_thresholds: Vec<ComponentSetting<f32>>,
+
+    /// Directory to keep graph histories in across restarts
+    #[clap(long = "state-dir", env = "OLED_STATE_DIR")]
+    state_dir: Option<PathBuf>,
+
     /// Display brightness. Possible values are bightest, bright, normal, dim, dimmest.
     #[cfg(feature = "i2c")]
     #[clap(short, long, default_value = "normal", value_parser = parse_brightness)]
@@ -386,16 +758,26 @@ fn main() {
     let mut drawer = Drawer::new_from_device_path(&args.device, args.brightness)
         .expect("Could not access display");
 
+    drawer.set_fonts(args.font, args.component_fonts);
+    drawer.set_graph_styles(args.graph_modes, args.graph_thresholds);
+
     let mut components: Vec<Box<dyn Component>> = Vec::with_capacity(8);
     components.push(Box::new(Hostname {
-        hostname: args.hostname,
+        hostname: args.hostname.clone(),
     }));
     components.push(Box::new(Uptime::new()));
 
+    if let Some(clock) = &args.clock {
+        components.push(Box::new(
+            Clock::new(clock, &args.clock_timezone, args.clock_large)
+                .expect("Could not set up clock"),
+        ));
+    }
+
     components.extend(
         known_disks
             .iter()
-            .map(|path| Disk::new_from_path(path))
+            .map(|path| Disk::new_from_path(path, &args.procfs))
             .filter_map(|disk| -> Option<Box<dyn Component>> {
                 match disk {
                     Ok(d) => Some(Box::new(d)),
@@ -413,50 +795,196 @@ fn main() {
 
     if args.memory {
         components.push(Box::new(
-            Memory::new().expect("Could not collect memory stats"),
+            Memory::new(&args.procfs, &args.block_sysfs, args.memory_swap)
+                .expect("Could not collect memory stats"),
+        ));
+    }
+
+    if args.pressure {
+        components.push(Box::new(
+            Pressure::new(&args.procfs, args.pressure_alert)
+                .expect("Could not collect pressure stats"),
         ));
     }
 
-    if let Some(network) = args.network {
+    let mut network_sources = Vec::new();
+    for pattern in &args.network {
+        network_sources.extend(
+            NetworkSource::resolve(pattern, &args.network_sysfs)
+                .expect("Could not find network adapters"),
+        );
+    }
+    if !network_sources.is_empty() {
+        let options = NetworkOptions {
+            graph: args.network_graph,
+            peak: args.network_peak,
+            label: network_sources.len() > 1 || args.network_rotate,
+            packets: args.network_packets,
+        };
+
+        match args.network_rotate {
+            true => components.push(Box::new(
+                NetworkThroughput::new(network_sources, options)
+                    .expect("Could not collect network stats"),
+            )),
+            false => {
+                for source in network_sources {
+                    components.push(Box::new(
+                        NetworkThroughput::new(vec![source], options)
+                            .expect("Could not collect network stats"),
+                    ));
+                }
+            }
+        }
+    }
+
+    if let Some(address) = args.address {
         components.push(Box::new(
-            NetworkThroughput::new(network, &args.network_sysfs)
-                .expect("Could not collect network stats"),
+            Address::new(address, &args.network_sysfs, args.address_ipv6)
+                .expect("Could not collect address info"),
         ));
     }
 
+    if let (Some(qr), Some(qr_interface)) = (args.qr, args.qr_interface) {
+        components.push(Box::new(
+            QrCode::new(&qr_interface, &args.network_sysfs, qr, args.qr_size)
+                .expect("Could not set up QR code"),
+        ));
+    }
+
+    for spec in args.commands {
+        components.push(Box::new(Command::new(
+            spec,
+            Duration::from_secs(args.command_timeout),
+        )));
+    }
+
+    for spec in args.status_files {
+        components.push(Box::new(
+            StatusFile::new(spec, args.status_file_max_age.map(Duration::from_secs))
+                .expect("Could not watch status file"),
+        ));
+    }
+
+    for spec in args.wireguard {
+        components.push(Box::new(WireGuard::new(
+            spec,
+            Duration::from_secs(args.wireguard_max_handshake_age),
+        )));
+    }
+
+    for target in args.probes {
+        components.push(Box::new(Probe::new(
+            target,
+            Duration::from_secs(args.probe_interval),
+            Duration::from_secs(args.probe_timeout),
+        )));
+    }
+
+    if args.systemd || !args.systemd_units.is_empty() {
+        components.push(Box::new(Systemd::new(args.systemd_units)));
+    }
+
+    if args.updates {
+        components.push(Box::new(Updates::new(
+            args.updates_file,
+            args.reboot_required_file,
+        )));
+    }
+
+    for spec in args.ups {
+        components.push(Box::new(Ups::new(
+            spec,
+            Duration::from_secs(args.update_timeout),
+        )));
+    }
+
+    if args.containers {
+        components.push(Box::new(Containers::new(
+            args.container_socket,
+            Duration::from_secs(args.update_timeout),
+        )));
+    }
+
+    #[cfg(feature = "kubernetes")]
+    if args.kubernetes {
+        components.push(Box::new(components::Kubernetes::new(
+            &args.kubelet_url,
+            &args.kubernetes_api_url,
+            args.hostname.clone(),
+            Duration::from_secs(args.update_timeout),
+        )));
+    }
+
     components.push(Box::new(UpdateIndicator {}));
 
-    let mut last_updates: Vec<Instant> = vec![Instant::now(); components.len()];
+    let mut collector = Collector::new(Duration::from_secs(args.update_timeout));
+    let mut scheduler = Scheduler::new();
+    for mut c in components {
+        if let Some(state_dir) = &args.state_dir {
+            let path = history::state_file(state_dir, c.as_ref());
+            if let Some(history) = c.history_mut() {
+                if let Err(e) = history.load(&path, SystemTime::now()) {
+                    println!("Could not load {}: {}", path.display(), e);
+                }
+            }
+        }
 
-    for c in &mut components {
-        match c.update() {
-            Ok(_) => (),
-            Err(e) => println!("{}", e),
-        };
+        let configured = settings::lookup(&args.update_intervals, c.as_ref())
+            .map(|seconds| Duration::from_secs(*seconds));
+        collector
+            .add(c, configured)
+            .expect("Could not start update thread");
     }
+    // draw the first frame with data, like when updating inline
+    collector.wait();
 
     println!("Started");
 
+    let notifier = Notifier::from_env().expect("Could not set up systemd notifications");
+    let mut status: Option<String> = None;
     let epoch = Instant::now();
+    let mut last_save = Instant::now();
 
     while !signals::shutdown_requested() {
         let tick = epoch.elapsed();
 
-        for (i, c) in &mut components.iter_mut().enumerate() {
-            if c.should_update(Instant::now() - last_updates[i]) {
-                println!("Updating {}", &c);
-                match c.update() {
-                    Ok(_) => (),
-                    Err(e) => println!("{}", e),
-                };
-                last_updates[i] = Instant::now();
+        for (i, interval) in collector.collect() {
+            if let Some(interval) = interval {
+                scheduler.schedule(i, Instant::now() + interval);
             }
         }
 
+        let now = Instant::now();
+        while let Some(i) = scheduler.pop_due(now) {
+            collector.request(i);
+        }
+
         drawer
-            .draw(tick.as_secs(), &mut components)
+            .draw(tick.as_secs(), collector.components())
             .expect("Could not draw update");
 
+        // ready once the first frame is on the display
+        let current = notify::status(collector.components());
+        let notified = match &status {
+            None => notifier.ready(&current),
+            Some(previous) if *previous != current => notifier.status(&current),
+            Some(_) => Ok(()),
+        }
+        .and_then(|_| notifier.watchdog());
+        if let Err(e) = notified {
+            println!("Could not notify systemd: {}", e);
+        }
+        status = Some(current);
+
+        if let (Some(state_dir), true) = (
+            &args.state_dir,
+            last_save.elapsed() > history::SAVE_INTERVAL,
+        ) {
+            save_histories(state_dir, &collector);
+            last_save = Instant::now();
+        }
+
         // sleep until the next full second
         let pause =
             Duration::from_secs(1) - Duration::from_millis(epoch.elapsed().subsec_millis().into());
@@ -464,5 +992,23 @@ fn main() {
         std::thread::sleep(pause);
     }
 
+    if let Some(state_dir) = &args.state_dir {
+        save_histories(state_dir, &collector);
+    }
+
     println!("Stopping");
+    if let Err(e) = notifier.stopping() {
+        println!("Could not notify systemd: {}", e);
+    }
+}
+
+fn save_histories(state_dir: &Path, collector: &Collector) {
+    for (c, _) in collector.components() {
+        if let Some(history) = c.history() {
+            let path = history::state_file(state_dir, c);
+            if let Err(e) = history.save(&path) {
+                println!("Could not save {}: {}", path.display(), e);
+            }
+        }
+    }
 }
@@ -0,0 +1,153 @@
+use crate::components::Component;
+use crate::Error;
+
+use std::ffi::OsStr;
+use std::os::linux::net::SocketAddrExt;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::net::{SocketAddr, UnixDatagram};
+
+/// Tells systemd about the state of the service with the `sd_notify`
+/// protocol, for services with `Type=notify` and a `WatchdogSec=`. Without a
+/// `NOTIFY_SOCKET` every notification is silently dropped.
+pub struct Notifier {
+    target: Option<(UnixDatagram, SocketAddr)>,
+}
+
+impl Notifier {
+    /// Notifies the socket systemd passes in `NOTIFY_SOCKET`, if any
+    pub fn from_env() -> Result<Self, Error> {
+        Self::new(std::env::var_os("NOTIFY_SOCKET").as_deref())
+    }
+
+    /// Notifies the socket at `path`, in the abstract namespace if it starts
+    /// with `@`
+    pub fn new(path: Option<&OsStr>) -> Result<Self, Error> {
+        let Some(path) = path else {
+            return Ok(Self { target: None });
+        };
+
+        let address = match path.as_bytes().strip_prefix(b"@") {
+            Some(name) => SocketAddr::from_abstract_name(name)?,
+            None => SocketAddr::from_pathname(path)?,
+        };
+        Ok(Self {
+            target: Some((UnixDatagram::unbound()?, address)),
+        })
+    }
+
+    /// Startup is finished, with a first `status`
+    pub fn ready(&self, status: &str) -> Result<(), Error> {
+        self.send(&format!("READY=1\nSTATUS={}", status))
+    }
+
+    pub fn status(&self, status: &str) -> Result<(), Error> {
+        self.send(&format!("STATUS={}", status))
+    }
+
+    /// The service is still alive, systemd restarts it when these stop
+    pub fn watchdog(&self) -> Result<(), Error> {
+        self.send("WATCHDOG=1")
+    }
+
+    pub fn stopping(&self) -> Result<(), Error> {
+        self.send("STOPPING=1")
+    }
+
+    fn send(&self, state: &str) -> Result<(), Error> {
+        if let Some((socket, address)) = &self.target {
+            socket.send_to_addr(state.as_bytes(), address)?;
+        }
+        Ok(())
+    }
+}
+
+/// The status line for the displayed components, naming those that are
+/// stale
+pub fn status<'c>(components: impl Iterator<Item = (&'c dyn Component, bool)>) -> String {
+    let mut shown = Vec::new();
+    let mut stale = Vec::new();
+    for (c, is_stale) in components {
+        shown.push(c.to_string());
+        if is_stale {
+            stale.push(c.to_string());
+        }
+    }
+
+    match stale.is_empty() {
+        true => format!("Showing {}", shown.join(", ")),
+        false => format!("Showing {}; stale: {}", shown.join(", "), stale.join(", ")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::Hostname;
+
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    fn tempfile_dir(name: &str) -> PathBuf {
+        let root =
+            std::env::temp_dir().join(format!("oled-notify-test-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir(&root).unwrap();
+        root
+    }
+
+    fn receive(listener: &UnixDatagram) -> String {
+        let mut buffer = [0u8; 256];
+        let length = listener.recv(&mut buffer).unwrap();
+        String::from_utf8_lossy(&buffer[..length]).into_owned()
+    }
+
+    #[test]
+    fn sends_notifications_to_socket() {
+        let path = tempfile_dir("socket").join("notify");
+        let listener = UnixDatagram::bind(&path).unwrap();
+        listener
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let notifier = Notifier::new(Some(path.as_os_str())).unwrap();
+
+        notifier.ready("Showing Hostname").unwrap();
+        assert_eq!(receive(&listener), "READY=1\nSTATUS=Showing Hostname");
+        notifier.watchdog().unwrap();
+        assert_eq!(receive(&listener), "WATCHDOG=1");
+        notifier.stopping().unwrap();
+        assert_eq!(receive(&listener), "STOPPING=1");
+    }
+
+    #[test]
+    fn sends_to_abstract_sockets() {
+        let name = format!("oled-notify-test-{}", std::process::id());
+        let address = SocketAddr::from_abstract_name(name.as_bytes()).unwrap();
+        let listener = UnixDatagram::bind_addr(&address).unwrap();
+        listener
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let notifier = Notifier::new(Some(OsStr::new(&format!("@{}", name)))).unwrap();
+
+        notifier.status("Showing Load").unwrap();
+        assert_eq!(receive(&listener), "STATUS=Showing Load");
+    }
+
+    #[test]
+    fn drops_notifications_without_socket() {
+        let notifier = Notifier::new(None).unwrap();
+
+        assert!(notifier.watchdog().is_ok());
+    }
+
+    #[test]
+    fn names_stale_components_in_status() {
+        let hostname = Hostname { hostname: None };
+        let components: [(&dyn Component, bool); 2] = [(&hostname, false), (&hostname, true)];
+
+        assert_eq!(status(components[..1].iter().copied()), "Showing Hostname");
+        assert_eq!(
+            status(components.iter().copied()),
+            "Showing Hostname, Hostname; stale: Hostname"
+        );
+    }
+}
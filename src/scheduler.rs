@@ -0,0 +1,60 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::time::Instant;
+
+/// Keeps track of when each component is due for its next update. Components
+/// that aren't scheduled are never looked at.
+#[derive(Debug, Default)]
+pub struct Scheduler {
+    // min-heap of (due time, component index)
+    queue: BinaryHeap<Reverse<(Instant, usize)>>,
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Makes the component at `index` due at `due`.
+    pub fn schedule(&mut self, index: usize, due: Instant) {
+        self.queue.push(Reverse((due, index)));
+    }
+
+    /// Removes and returns the component that has been due the longest at
+    /// `now`, if any.
+    pub fn pop_due(&mut self, now: Instant) -> Option<usize> {
+        match self.queue.peek() {
+            Some(Reverse((due, _))) if *due <= now => {
+                self.queue.pop().map(|Reverse((_, index))| index)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn pops_due_components_in_order() {
+        let now = Instant::now();
+        let mut scheduler = Scheduler::new();
+        scheduler.schedule(0, now + Duration::from_secs(60));
+        scheduler.schedule(1, now + Duration::from_secs(5));
+        scheduler.schedule(2, now);
+
+        assert_eq!(scheduler.pop_due(now + Duration::from_secs(10)), Some(2));
+        assert_eq!(scheduler.pop_due(now + Duration::from_secs(10)), Some(1));
+        assert_eq!(scheduler.pop_due(now + Duration::from_secs(10)), None);
+        assert_eq!(scheduler.pop_due(now + Duration::from_secs(60)), Some(0));
+    }
+
+    #[test]
+    fn empty_scheduler_is_never_due() {
+        let mut scheduler = Scheduler::new();
+
+        assert_eq!(scheduler.pop_due(Instant::now()), None);
+    }
+}
@@ -0,0 +1,156 @@
+use crate::components::Component;
+use crate::Error;
+
+use embedded_graphics::mono_font::{ascii, MonoFont};
+
+/// A setting for the components with a certain name, given as NAME=VALUE.
+/// Names are matched case-insensitively against how components display
+/// themselves, e.g. `Load`, `Network eth0` or the name of a disk.
+#[derive(Clone, Debug)]
+pub struct ComponentSetting<T> {
+    pub name: String,
+    pub value: T,
+}
+
+impl<T> std::str::FromStr for ComponentSetting<T>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    type Err = Error;
+
+    fn from_str(setting: &str) -> Result<Self, Self::Err> {
+        let (name, value) = setting
+            .rsplit_once('=')
+            .ok_or_else(|| format!("'{}' is not in the format NAME=VALUE", setting))?;
+
+        if name.is_empty() {
+            return Err("component name must not be empty".into());
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            value: value
+                .parse()
+                .map_err(|e| format!("Invalid value for {}: {}", name, e))?,
+        })
+    }
+}
+
+/// The value of the last setting matching `component`.
+pub fn lookup<'s, T>(
+    settings: &'s [ComponentSetting<T>],
+    component: &dyn Component,
+) -> Option<&'s T> {
+    let name = component.to_string();
+    settings
+        .iter()
+        .rev()
+        .find(|setting| setting.name.eq_ignore_ascii_case(&name))
+        .map(|setting| &setting.value)
+}
+
+/// The values of all settings matching `component`, for settings that can
+/// be given more than once.
+pub fn matching<'s, T>(
+    settings: &'s [ComponentSetting<T>],
+    component: &dyn Component,
+) -> impl Iterator<Item = &'s T> {
+    let name = component.to_string();
+    settings
+        .iter()
+        .filter(move |setting| setting.name.eq_ignore_ascii_case(&name))
+        .map(|setting| &setting.value)
+}
+
+/// One of embedded-graphics' ASCII fonts, named by their size like `6x10`
+#[derive(Clone, Copy)]
+pub struct Font(pub &'static MonoFont<'static>);
+
+impl std::fmt::Debug for Font {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Font({}x{})",
+            self.0.character_size.width, self.0.character_size.height
+        )
+    }
+}
+
+impl std::str::FromStr for Font {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Ok(Self(match name.to_lowercase().as_str() {
+            "4x6" => &ascii::FONT_4X6,
+            "5x7" => &ascii::FONT_5X7,
+            "5x8" => &ascii::FONT_5X8,
+            "6x9" => &ascii::FONT_6X9,
+            "6x10" => &ascii::FONT_6X10,
+            "6x12" => &ascii::FONT_6X12,
+            "7x13" => &ascii::FONT_7X13,
+            "8x13" => &ascii::FONT_8X13,
+            "9x15" => &ascii::FONT_9X15,
+            "10x20" => &ascii::FONT_10X20,
+            unknown => {
+                return Err(format!(
+                    "{} is not a known font, use one of 4x6, 5x7, 5x8, 6x9, 6x10, 6x12, 7x13, 8x13, 9x15 or 10x20",
+                    unknown
+                )
+                .into())
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_component_settings() {
+        let setting: ComponentSetting<Font> = "Network eth0=4x6".parse().unwrap();
+
+        assert_eq!(setting.name, "Network eth0");
+        assert_eq!(setting.value.0.character_size.width, 4);
+    }
+
+    #[test]
+    fn last_matching_setting_wins() {
+        let settings: Vec<ComponentSetting<Font>> = vec![
+            "hostname=4x6".parse().unwrap(),
+            "Load=10x20".parse().unwrap(),
+            "HOSTNAME=5x8".parse().unwrap(),
+        ];
+        let hostname = crate::components::Hostname {
+            hostname: Some("nas".to_string()),
+        };
+
+        assert_eq!(
+            lookup(&settings, &hostname).unwrap().0.character_size.width,
+            5
+        );
+    }
+
+    #[test]
+    fn collects_all_matching_settings() {
+        let settings: Vec<ComponentSetting<f32>> = vec![
+            "hostname=1".parse().unwrap(),
+            "Load=2".parse().unwrap(),
+            "HOSTNAME=3".parse().unwrap(),
+        ];
+        let hostname = crate::components::Hostname { hostname: None };
+
+        assert_eq!(
+            matching(&settings, &hostname).copied().collect::<Vec<_>>(),
+            vec![1.0, 3.0]
+        );
+    }
+
+    #[test]
+    fn rejects_bad_component_settings() {
+        assert!("Load".parse::<ComponentSetting<Font>>().is_err());
+        assert!("=6x10".parse::<ComponentSetting<Font>>().is_err());
+        assert!("Load=huge".parse::<ComponentSetting<Font>>().is_err());
+    }
+}